@@ -1,13 +1,19 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use std::{collections::BTreeMap, sync::Arc};
-use zellij_tile::prelude::{ModeInfo, TabInfo};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+use zellij_tile::prelude::{ModeInfo, PaneInfo, PaneManifest, SessionInfo, TabInfo};
 
 use zjstatus::{
-    config::{ModuleConfig, ZellijState},
+    config::{Dependency, ModuleConfig, ZellijState},
     render::{
         formatted_part_from_string_cached, formatted_parts_from_string_cached, FormattedPart,
     },
-    widgets::{datetime::DateTimeWidget, mode::ModeWidget, session::SessionWidget, widget::Widget},
+    widgets::{
+        datetime::DateTimeWidget, mode::ModeWidget, session::SessionWidget, tabs::TabsWidget,
+        widget::Widget,
+    },
 };
 
 fn bench_moduleconfig_render_bar(c: &mut Criterion) {
@@ -53,7 +59,99 @@ fn bench_moduleconfig_render_bar(c: &mut Criterion) {
     };
 
     c.bench_function("ModuleConfig::render_bar", |b| {
-        b.iter(|| module_config.render_bar(state.clone(), widgets.clone()))
+        b.iter(|| module_config.render_bar(&state, &widgets))
+    });
+}
+
+fn large_state(tab_count: usize, pane_count: usize) -> ZellijState {
+    let tabs: Vec<TabInfo> = (0..tab_count)
+        .map(|position| TabInfo {
+            position,
+            name: format!("tab {position}"),
+            active: position == 0,
+            ..Default::default()
+        })
+        .collect();
+
+    let panes = PaneManifest {
+        panes: HashMap::from_iter((0..tab_count).map(|position| {
+            let panes = (0..pane_count)
+                .map(|id| PaneInfo {
+                    id: id as u32,
+                    title: format!("pane {id}"),
+                    is_focused: id == 0,
+                    ..Default::default()
+                })
+                .collect();
+
+            (position, panes)
+        })),
+    };
+
+    let sessions = (0..10)
+        .map(|index| SessionInfo {
+            name: format!("session {index}"),
+            tabs: tabs.clone(),
+            panes: panes.clone(),
+            is_current_session: index == 0,
+            ..Default::default()
+        })
+        .collect();
+
+    ZellijState {
+        cols: 200,
+        mode: ModeInfo::default(),
+        tabs,
+        panes,
+        sessions,
+        ..Default::default()
+    }
+}
+
+fn bench_moduleconfig_render_bar_large_state(c: &mut Criterion) {
+    let config = BTreeMap::from([
+        ("format_left".to_owned(), "{mode} #[fg=#89B4FA,bg=#181825,bold]{session}".to_owned()),
+        ("format_center".to_owned(), "{tabs}".to_owned()),
+        ("format_right".to_owned(), "{datetime}".to_owned()),
+        ("format_space".to_owned(), "#[bg=#181825]".to_owned()),
+        ("tab_normal".to_owned(), "#[fg=#6C7086] {name} ".to_owned()),
+        ("tab_active".to_owned(), "#[fg=#9399B2,bold] {name} ".to_owned()),
+    ]);
+
+    let mut module_config = ModuleConfig::new(&config).unwrap();
+
+    let mut widgets: BTreeMap<String, Arc<dyn Widget>> = BTreeMap::new();
+
+    widgets.insert("mode".to_owned(), Arc::new(ModeWidget::new(&config)));
+    widgets.insert("datetime".to_owned(), Arc::new(DateTimeWidget::new(&config)));
+    widgets.insert("session".to_owned(), Arc::new(SessionWidget::new(&config)));
+    widgets.insert("tabs".to_owned(), Arc::new(TabsWidget::new(&config)));
+
+    let state = large_state(50, 20);
+
+    // renders on a timer tick, where the tabs section is reused
+    c.bench_function("ModuleConfig::render_bar (large state)", |b| {
+        b.iter(|| module_config.render_bar(&state, &widgets))
+    });
+
+    // renders on a tab update, where every section is rendered again
+    let mut tab_update = state.clone();
+
+    c.bench_function("ModuleConfig::render_bar (large state, tab update)", |b| {
+        b.iter(|| {
+            tab_update.mark_changed(Dependency::Tabs);
+            module_config.render_bar(&tab_update, &widgets)
+        })
+    });
+
+    // the previous approach of cloning the state and widgets on every render,
+    // without any output from previous renders
+    c.bench_function("ModuleConfig::render_bar (large state, cloned)", |b| {
+        b.iter_batched(
+            || ModuleConfig::new(&config).unwrap(),
+            |mut module_config| module_config.render_bar(&tab_update.clone(), &widgets.clone()),
+            BatchSize::SmallInput,
+        )
     });
 }
 
@@ -86,19 +184,21 @@ fn bench_formattedpart_format_string_with_widgets(c: &mut Criterion) {
         Arc::new(SessionWidget::new(&BTreeMap::from([]))),
     );
 
-    let state = ZellijState {
+    let mut state = ZellijState {
         mode: ModeInfo::default(),
         tabs: vec![TabInfo {
             name: "test".to_owned(),
             active: true,
             ..Default::default()
         }],
-        cache_mask: 0b00000011,
         ..Default::default()
     };
 
     c.bench_function("FormattedPart::format_string_with_widgets", |b| {
-        b.iter(|| format.format_string_with_widgets(&widgets, &state))
+        b.iter(|| {
+            state.mark_changed(Dependency::Mode);
+            format.format_string_with_widgets(&widgets, &state)
+        })
     });
 }
 
@@ -166,6 +266,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     bench_formattedpart_format_string_with_widgets(c);
     bench_moduleconfig_new(c);
     bench_moduleconfig_render_bar(c);
+    bench_moduleconfig_render_bar_large_state(c);
 }
 
 criterion_group!(benches, criterion_benchmark);
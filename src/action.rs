@@ -0,0 +1,257 @@
+#[cfg(all(not(feature = "bench"), not(test)))]
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use anyhow::{Result, bail};
+use zellij_tile::prelude::InputMode;
+#[cfg(all(not(feature = "bench"), not(test)))]
+use zellij_tile::{
+    prelude::MessageToPlugin,
+    shim::{
+        close_focused_tab, detach, go_to_next_tab, go_to_previous_tab, hide_floating_panes,
+        new_tab, next_swap_layout, open_plugin_pane_floating, pipe_message_to_plugin,
+        previous_swap_layout, run_command, show_floating_panes, switch_tab_to,
+        switch_to_input_mode, toggle_focus_fullscreen, toggle_pane_frames,
+    },
+};
+
+use crate::{
+    config::ZellijState,
+    widgets::{command::commandline_parser, widget::MouseAction},
+};
+
+/// Suffixes of the keys, that configure actions for mouse events over a
+/// widget, e.g. `command_git_clickaction_right` or `mode_scrollup`.
+pub const MOUSE_ACTION_KEYS: [(&str, MouseAction); 4] = [
+    ("_clickaction", MouseAction::LeftClick),
+    ("_clickaction_right", MouseAction::RightClick),
+    ("_scrollup", MouseAction::ScrollUp),
+    ("_scrolldown", MouseAction::ScrollDown),
+];
+
+/// Splits a key like `mode_scrollup` into the widget key and the mouse
+/// action.
+pub fn parse_mouse_action_key(key: &str) -> Option<(&str, MouseAction)> {
+    MOUSE_ACTION_KEYS
+        .iter()
+        .find_map(|(suffix, action)| Some((key.strip_suffix(suffix)?, *action)))
+}
+
+/// Action, that is run on a mouse event. Actions prefixed with `zellij:` call
+/// the plugin API, e.g. `zellij:switch_mode locked`, and `pipe:<name> <payload>`
+/// sends a message to all plugins. Anything else is run as a command.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClickAction {
+    Zellij(ZellijAction),
+    Pipe {
+        name: String,
+        payload: Option<String>,
+    },
+    Command(Vec<String>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ZellijAction {
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    GoToTab(u32),
+    ToggleFloatingPanes,
+    ToggleFullscreen,
+    TogglePaneFrames,
+    SwitchMode(InputMode),
+    NextSwapLayout,
+    PreviousSwapLayout,
+    OpenSessionManager,
+    Detach,
+}
+
+impl FromStr for ClickAction {
+    fn from_str(action: &str) -> Result<Self> {
+        if let Some(pipe) = action.trim().strip_prefix("pipe:") {
+            let (name, payload) = match pipe.split_once(' ') {
+                Some((name, payload)) => (name, Some(payload.trim().to_owned())),
+                None => (pipe, None),
+            };
+
+            if name.is_empty() {
+                bail!("expected a pipe name in {}", action);
+            }
+
+            return Ok(ClickAction::Pipe {
+                name: name.to_owned(),
+                payload: payload.filter(|payload| !payload.is_empty()),
+            });
+        }
+
+        let Some(zellij_action) = action.trim().strip_prefix("zellij:") else {
+            return Ok(ClickAction::Command(commandline_parser(action)));
+        };
+
+        let args: Vec<&str> = zellij_action.split_whitespace().collect();
+
+        let zellij_action = match args.as_slice() {
+            ["new_tab"] => ZellijAction::NewTab,
+            ["close_tab"] => ZellijAction::CloseTab,
+            ["next_tab"] => ZellijAction::NextTab,
+            ["previous_tab"] => ZellijAction::PreviousTab,
+            ["go_to_tab", index] => match index.parse::<u32>() {
+                Ok(index) if index > 0 => ZellijAction::GoToTab(index),
+                _ => bail!("expected a tab index in {}", action),
+            },
+            ["toggle_floating_panes"] => ZellijAction::ToggleFloatingPanes,
+            ["toggle_fullscreen"] => ZellijAction::ToggleFullscreen,
+            ["toggle_pane_frames"] => ZellijAction::TogglePaneFrames,
+            ["switch_mode", mode] => match InputMode::from_str(mode) {
+                Ok(mode) => ZellijAction::SwitchMode(mode),
+                Err(_) => bail!("unknown mode in {}", action),
+            },
+            ["next_swap_layout"] => ZellijAction::NextSwapLayout,
+            ["previous_swap_layout"] => ZellijAction::PreviousSwapLayout,
+            ["open_session_manager"] => ZellijAction::OpenSessionManager,
+            ["detach"] => ZellijAction::Detach,
+            _ => bail!("unknown zellij action {}", action),
+        };
+
+        Ok(ClickAction::Zellij(zellij_action))
+    }
+
+    type Err = anyhow::Error;
+}
+
+impl ClickAction {
+    #[allow(unused_variables)]
+    pub fn run(&self, state: &ZellijState) {
+        let action = match self {
+            ClickAction::Command(command) => {
+                if command.is_empty() {
+                    return;
+                }
+
+                tracing::debug!("Running command {:?}", command);
+
+                #[cfg(all(not(feature = "bench"), not(test)))]
+                run_command(
+                    &command.iter().map(|x| x.as_str()).collect::<Vec<&str>>(),
+                    BTreeMap::new(),
+                );
+
+                return;
+            }
+            ClickAction::Pipe { name, payload } => {
+                tracing::debug!("Sending pipe message {} {:?}", name, payload);
+
+                #[cfg(all(not(feature = "bench"), not(test)))]
+                pipe_message_to_plugin(match payload {
+                    Some(payload) => MessageToPlugin::new(name).with_payload(payload),
+                    None => MessageToPlugin::new(name),
+                });
+
+                return;
+            }
+            ClickAction::Zellij(action) => action,
+        };
+
+        tracing::debug!("Running zellij action {:?}", action);
+
+        #[cfg(all(not(feature = "bench"), not(test)))]
+        run_zellij_action(action, state);
+    }
+}
+
+#[cfg(all(not(feature = "bench"), not(test)))]
+fn run_zellij_action(action: &ZellijAction, state: &ZellijState) {
+    match action {
+        ZellijAction::NewTab => {
+            new_tab::<&str>(None, None);
+        }
+        ZellijAction::CloseTab => close_focused_tab(),
+        ZellijAction::NextTab => go_to_next_tab(),
+        ZellijAction::PreviousTab => go_to_previous_tab(),
+        ZellijAction::GoToTab(index) => switch_tab_to(*index),
+        ZellijAction::ToggleFloatingPanes => {
+            let visible = state
+                .tabs
+                .iter()
+                .find(|tab| tab.active)
+                .is_some_and(|tab| tab.are_floating_panes_visible);
+
+            let result = match visible {
+                true => hide_floating_panes(None),
+                false => show_floating_panes(None),
+            };
+
+            if let Err(e) = result {
+                tracing::error!("cannot toggle floating panes: {}", e);
+            }
+        }
+        ZellijAction::ToggleFullscreen => toggle_focus_fullscreen(),
+        ZellijAction::TogglePaneFrames => toggle_pane_frames(),
+        ZellijAction::SwitchMode(mode) => switch_to_input_mode(mode),
+        ZellijAction::NextSwapLayout => next_swap_layout(),
+        ZellijAction::PreviousSwapLayout => previous_swap_layout(),
+        ZellijAction::OpenSessionManager => {
+            open_plugin_pane_floating(
+                "zellij:session-manager",
+                BTreeMap::new(),
+                None,
+                BTreeMap::new(),
+            );
+        }
+        ZellijAction::Detach => detach(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("zellij:new_tab", Some(ClickAction::Zellij(ZellijAction::NewTab)))]
+    #[case(
+        "zellij:switch_mode locked",
+        Some(ClickAction::Zellij(ZellijAction::SwitchMode(InputMode::Locked)))
+    )]
+    #[case(
+        "zellij:go_to_tab 2",
+        Some(ClickAction::Zellij(ZellijAction::GoToTab(2)))
+    )]
+    #[case(
+        "zellij:open_session_manager",
+        Some(ClickAction::Zellij(ZellijAction::OpenSessionManager))
+    )]
+    #[case(
+        "bash -c \"echo test\"",
+        Some(ClickAction::Command(vec!["bash".to_owned(), "-c".to_owned(), "echo test".to_owned()]))
+    )]
+    #[case(
+        "pipe:zjstatus zjstatus::pipe::pipe_status::clicked",
+        Some(ClickAction::Pipe {
+            name: "zjstatus".to_owned(),
+            payload: Some("zjstatus::pipe::pipe_status::clicked".to_owned()),
+        })
+    )]
+    #[case("pipe: payload", None)]
+    #[case("zellij:go_to_tab 0", None)]
+    #[case("zellij:switch_mode unknown", None)]
+    #[case("zellij:new_tab now", None)]
+    #[case("zellij:unknown", None)]
+    fn test_parse_click_action(#[case] input: &str, #[case] expected: Option<ClickAction>) {
+        assert_eq!(input.parse::<ClickAction>().ok(), expected);
+    }
+
+    #[test]
+    fn test_parse_mouse_action_key() {
+        assert_eq!(
+            parse_mouse_action_key("command_git_clickaction_right"),
+            Some(("command_git", MouseAction::RightClick))
+        );
+        assert_eq!(
+            parse_mouse_action_key("mode_scrollup"),
+            Some(("mode", MouseAction::ScrollUp))
+        );
+        assert_eq!(parse_mouse_action_key("mode_normal"), None);
+    }
+}
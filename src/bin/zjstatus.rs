@@ -1,12 +1,14 @@
 use zellij_tile::prelude::*;
 
-use chrono::Local;
+use chrono::{DateTime, Duration, Local};
 use std::{collections::BTreeMap, sync::Arc};
 use uuid::Uuid;
 
 use zjstatus::{
-    config::{self, ModuleConfig, UpdateEventMask, ZellijState},
+    config::{self, Dependency, ModuleConfig, ZellijState},
     frames, pipe,
+    render::clear_format_caches,
+    theme::{uses_theme_colors, with_theme_colors},
     widgets::{
         command::{CommandResult, CommandWidget},
         datetime::DateTimeWidget,
@@ -14,14 +16,18 @@ use zjstatus::{
         notification::NotificationWidget,
         pipe::PipeWidget,
         session::SessionWidget,
+        spinner::SpinnerWidget,
         swap_layout::SwapLayoutWidget,
         tabs::TabsWidget,
+        tooltip::TooltipWidget,
         widget::Widget,
     },
 };
 
-// Matches the old incidental Zellij session scan cadence.
-const REFRESH_INTERVAL_SECONDS: f64 = 1.0;
+// Lower bound for timers, so that overdue updates do not spin the plugin.
+const MIN_TIMEOUT_SECONDS: f64 = 0.01;
+// Time after which a timer, that did not fire, is considered lost.
+const TIMER_GRACE_SECONDS: i64 = 5;
 
 #[derive(Default)]
 struct State {
@@ -31,6 +37,9 @@ struct State {
     userspace_configuration: BTreeMap<String, String>,
     module_config: config::ModuleConfig,
     widget_map: BTreeMap<String, Arc<dyn Widget>>,
+    // deadlines of the timers, that have been set and did not fire yet, the
+    // earliest first
+    timers: Vec<DateTime<Local>>,
     err: Option<anyhow::Error>,
 }
 
@@ -78,16 +87,18 @@ impl ZellijPlugin for State {
             EventType::SessionUpdate,
             EventType::RunCommandResult,
         ]);
-        set_timeout(REFRESH_INTERVAL_SECONDS);
+        // theme colors are resolved with the default theme until the first
+        // ModeUpdate arrives
+        let themed_configuration = with_theme_colors(&configuration, &Styling::default());
 
-        self.module_config = match ModuleConfig::new(&configuration) {
+        self.module_config = match ModuleConfig::new(&themed_configuration) {
             Ok(mc) => mc,
             Err(e) => {
                 self.err = Some(e);
                 return;
             }
         };
-        self.widget_map = register_widgets(&configuration);
+        self.widget_map = register_widgets(&themed_configuration);
         self.userspace_configuration = configuration;
         self.pending_events = Vec::new();
         self.got_permissions = false;
@@ -95,6 +106,7 @@ impl ZellijPlugin for State {
 
         self.state = ZellijState {
             cols: 0,
+            rows: 0,
             command_results: BTreeMap::new(),
             pipe_results: BTreeMap::new(),
             mode: ModeInfo::default(),
@@ -103,9 +115,14 @@ impl ZellijPlugin for State {
             tabs: Vec::new(),
             sessions: Vec::new(),
             start_time: Local::now(),
-            cache_mask: 0,
             incoming_notification: None,
+            background: false,
+            hover: None,
+            revision: 0,
+            changes: BTreeMap::new(),
         };
+
+        self.schedule_update();
     }
 
     fn pipe(&mut self, pipe_message: PipeMessage) -> bool {
@@ -129,7 +146,7 @@ impl ZellijPlugin for State {
             }
         }
 
-        should_render
+        should_render && !self.state.background
     }
 
     #[tracing::instrument(skip_all, fields(event_type))]
@@ -152,11 +169,12 @@ impl ZellijPlugin for State {
             return false;
         }
 
-        self.handle_event(event)
+        // instances in inactive tabs are rendered, once their tab is active
+        self.handle_event(event) && !self.state.background
     }
 
     #[tracing::instrument(skip_all)]
-    fn render(&mut self, _rows: usize, cols: usize) {
+    fn render(&mut self, rows: usize, cols: usize) {
         if !self.got_permissions {
             return;
         }
@@ -168,18 +186,126 @@ impl ZellijPlugin for State {
         }
 
         self.state.cols = cols;
+        self.state.rows = rows;
 
         tracing::debug!("{:?}", self.state.mode.session_name);
 
-        let output = self
-            .module_config
-            .render_bar(self.state.clone(), self.widget_map.clone());
+        let output = self.module_config.render_bar(&self.state, &self.widget_map);
 
         print!("{}", output);
+
+        self.schedule_update();
     }
 }
 
 impl State {
+    /// Parses the configuration again with the colors of the current theme.
+    /// The state, that has been built up at runtime, is kept.
+    fn reload_theme(&mut self) {
+        tracing::debug!("reloading theme colors");
+
+        clear_format_caches();
+
+        let configuration =
+            with_theme_colors(&self.userspace_configuration, &self.state.mode.style.colors);
+
+        let mut module_config = match ModuleConfig::new(&configuration) {
+            Ok(mc) => mc,
+            Err(e) => {
+                self.err = Some(e);
+                return;
+            }
+        };
+        module_config.restore_runtime_state(&self.module_config);
+        self.module_config = module_config;
+
+        let widget_map = register_widgets(&configuration);
+        for (name, widget) in &widget_map {
+            if let Some(state) = self
+                .widget_map
+                .get(name)
+                .and_then(|previous| previous.runtime_state())
+            {
+                widget.restore_runtime_state(state);
+            }
+        }
+        self.widget_map = widget_map;
+    }
+
+    /// Checks whether the instance sits in the active tab. When it becomes
+    /// active, time based widgets are rendered again, since no timers fired
+    /// in the background.
+    fn update_background(&mut self) {
+        let background = match frames::is_plugin_in_active_tab(
+            &self.state.tabs,
+            &self.state.panes,
+            get_plugin_ids(),
+        ) {
+            Some(active) => !active,
+            None => return,
+        };
+
+        if self.state.background && !background {
+            tracing::debug!("tab became active");
+            self.mark_expired();
+        }
+
+        self.state.background = background;
+    }
+
+    /// Marks the clock and everything, that the widgets report as expired, as
+    /// changed. The hover is cleared, once it expired.
+    fn mark_expired(&mut self) {
+        self.state.mark_changed(Dependency::Clock);
+        self.state.expire_hover();
+
+        let expired: Vec<Dependency> = self
+            .widget_map
+            .values()
+            .flat_map(|widget| widget.expired(&self.state))
+            .collect();
+
+        for dependency in expired {
+            self.state.mark_changed(dependency);
+        }
+    }
+
+    /// Sets a timer for the earliest update any widget or the hover is waiting
+    /// for, unless a timer fires before anyway.
+    fn schedule_update(&mut self) {
+        if self.state.background {
+            return;
+        }
+
+        let next_update = match self
+            .widget_map
+            .values()
+            .filter_map(|widget| widget.next_update(&self.state))
+            .chain(self.state.hover.as_ref().map(|hover| hover.expires_at()))
+            .min()
+        {
+            Some(next_update) => next_update,
+            None => return,
+        };
+
+        // timers, that are long overdue, are not waited for
+        let now = Local::now();
+        self.timers
+            .retain(|timer| *timer + Duration::seconds(TIMER_GRACE_SECONDS) > now);
+
+        if self.timers.iter().any(|timer| *timer <= next_update) {
+            return;
+        }
+
+        let timeout =
+            ((next_update - now).num_milliseconds() as f64 / 1000.0).max(MIN_TIMEOUT_SECONDS);
+
+        tracing::debug!(timeout = ?timeout, "scheduling update");
+
+        set_timeout(timeout);
+        self.timers.insert(0, next_update.max(now));
+    }
+
     fn handle_event(&mut self, event: Event) -> bool {
         let mut should_render = false;
         match event {
@@ -187,10 +313,10 @@ impl State {
                 tracing::Span::current().record("event_type", "Event::Mouse");
                 tracing::debug!(mouse = ?mouse_info);
 
-                self.module_config.handle_mouse_action(
-                    self.state.clone(),
+                should_render = self.module_config.handle_mouse_action(
+                    &mut self.state,
                     mouse_info,
-                    self.widget_map.clone(),
+                    &self.widget_map,
                 );
             }
             Event::ModeUpdate(mode_info) => {
@@ -198,8 +324,14 @@ impl State {
                 tracing::debug!(mode = ?mode_info.mode);
                 tracing::debug!(mode = ?mode_info.session_name);
 
+                let theme_changed = mode_info.style.colors != self.state.mode.style.colors;
+
                 self.state.mode = mode_info;
-                self.state.cache_mask = UpdateEventMask::Mode as u8;
+                self.state.mark_changed(Dependency::Mode);
+
+                if theme_changed && uses_theme_colors(&self.userspace_configuration) {
+                    self.reload_theme();
+                }
 
                 should_render = true;
             }
@@ -222,7 +354,8 @@ impl State {
                 );
 
                 self.state.panes = pane_info;
-                self.state.cache_mask = UpdateEventMask::Tab as u8;
+                self.state.mark_changed(Dependency::Panes);
+                self.update_background();
 
                 should_render = true;
             }
@@ -230,6 +363,9 @@ impl State {
                 tracing::Span::current().record("event_type", "Event::PermissionRequestResult");
                 tracing::debug!(result = ?result);
                 set_selectable(false);
+
+                // render the events, that have been cached until now
+                should_render = true;
             }
             Event::RunCommandResult(exit_code, stdout, stderr, context) => {
                 tracing::Span::current().record("event_type", "Event::RunCommandResult");
@@ -240,9 +376,10 @@ impl State {
                     context = ?context
                 );
 
-                self.state.cache_mask = UpdateEventMask::Command as u8;
-
                 if let Some(name) = context.get("name") {
+                    self.state
+                        .mark_changed(Dependency::Command(name.to_owned()));
+
                     let stdout = match String::from_utf8(stdout) {
                         Ok(s) => s,
                         Err(_) => "".to_owned(),
@@ -263,6 +400,8 @@ impl State {
                         },
                     );
                 }
+
+                should_render = true;
             }
             Event::SessionUpdate(session_info, _) => {
                 tracing::Span::current().record("event_type", "Event::SessionUpdate");
@@ -286,7 +425,7 @@ impl State {
                 }
 
                 self.state.sessions = session_info;
-                self.state.cache_mask = UpdateEventMask::Session as u8;
+                self.state.mark_changed(Dependency::Sessions);
 
                 should_render = true;
             }
@@ -294,15 +433,21 @@ impl State {
                 tracing::Span::current().record("event_type", "Event::TabUpdate");
                 tracing::debug!(tab_count = ?tab_info.len());
 
-                self.state.cache_mask = UpdateEventMask::Tab as u8;
                 self.state.tabs = tab_info;
+                self.state.mark_changed(Dependency::Tabs);
+                self.update_background();
 
                 should_render = true;
             }
             Event::Timer(_) => {
                 tracing::Span::current().record("event_type", "Event::Timer");
-                set_timeout(REFRESH_INTERVAL_SECONDS);
-                self.state.cache_mask = 0;
+
+                // timers fire in the order of their deadlines, the next one is
+                // scheduled on render
+                if !self.timers.is_empty() {
+                    self.timers.remove(0);
+                }
+                self.mark_expired();
 
                 should_render = true;
             }
@@ -333,7 +478,15 @@ fn register_widgets(configuration: &BTreeMap<String, String>) -> BTreeMap<String
         "session".to_owned(),
         Arc::new(SessionWidget::new(configuration)),
     );
+    widget_map.insert(
+        "spinner".to_owned(),
+        Arc::new(SpinnerWidget::new(configuration)),
+    );
     widget_map.insert("tabs".to_owned(), Arc::new(TabsWidget::new(configuration)));
+    widget_map.insert(
+        "tooltip".to_owned(),
+        Arc::new(TooltipWidget::new(configuration)),
+    );
     widget_map.insert(
         "notifications".to_owned(),
         Arc::new(NotificationWidget::new(configuration)),
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use anstyle::{Ansi256Color, AnsiColor, Color, RgbColor};
+
+/// Levels of the 6x6x6 color cube of the 256 color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Default values of the 16 ansi colors, as used by xterm.
+const ANSI_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Amount of colors the terminal supports. Colors are mapped to the nearest
+/// color of the palette on render, `None` drops all colors but keeps effects.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorDepth {
+    #[default]
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    None,
+}
+
+impl ColorDepth {
+    pub fn parse(depth: &str) -> Option<Self> {
+        match depth {
+            "truecolor" => Some(ColorDepth::TrueColor),
+            "256" => Some(ColorDepth::Ansi256),
+            "16" => Some(ColorDepth::Ansi16),
+            "none" => Some(ColorDepth::None),
+            _ => None,
+        }
+    }
+
+    /// Reads `color_depth` from the configuration. Invalid values are
+    /// reported on load and fall back to truecolor.
+    pub fn from_config(config: &BTreeMap<String, String>) -> Self {
+        config
+            .get("color_depth")
+            .and_then(|depth| ColorDepth::parse(depth))
+            .unwrap_or_default()
+    }
+
+    pub fn convert(&self, color: Option<Color>) -> Option<Color> {
+        let color = color?;
+
+        match (self, color) {
+            (ColorDepth::None, _) => None,
+            (ColorDepth::TrueColor, _) => Some(color),
+            (_, Color::Ansi(_)) => Some(color),
+            (ColorDepth::Ansi256, Color::Ansi256(_)) => Some(color),
+            (ColorDepth::Ansi256, Color::Rgb(rgb)) => Some(nearest_ansi256(rgb).into()),
+            (ColorDepth::Ansi16, Color::Ansi256(color)) => match color.into_ansi() {
+                Some(ansi) => Some(ansi.into()),
+                None => Some(nearest_ansi16(ansi256_to_rgb(color)).into()),
+            },
+            (ColorDepth::Ansi16, Color::Rgb(rgb)) => Some(nearest_ansi16(rgb).into()),
+        }
+    }
+}
+
+/// Converts a color to rgb. Ansi colors are approximated with their xterm
+/// defaults, since the actual values depend on the terminal.
+pub fn to_rgb(color: Color) -> RgbColor {
+    match color {
+        Color::Rgb(rgb) => rgb,
+        Color::Ansi256(color) => ansi256_to_rgb(color),
+        Color::Ansi(color) => ansi256_to_rgb(Ansi256Color::from_ansi(color)),
+    }
+}
+
+/// Mixes `percent` of the `other` color into the color.
+pub fn mix(color: RgbColor, other: RgbColor, percent: u8) -> RgbColor {
+    let percent = percent.min(100) as u32;
+    let channel = |a: u8, b: u8| ((a as u32 * (100 - percent) + b as u32 * percent) / 100) as u8;
+
+    RgbColor(
+        channel(color.0, other.0),
+        channel(color.1, other.1),
+        channel(color.2, other.2),
+    )
+}
+
+pub fn darken(color: RgbColor, percent: u8) -> RgbColor {
+    mix(color, RgbColor(0, 0, 0), percent)
+}
+
+pub fn lighten(color: RgbColor, percent: u8) -> RgbColor {
+    mix(color, RgbColor(255, 255, 255), percent)
+}
+
+/// Returns black or white, whichever is more readable on the color.
+pub fn contrast(color: RgbColor) -> RgbColor {
+    let luminance = 299 * color.0 as u32 + 587 * color.1 as u32 + 114 * color.2 as u32;
+
+    match luminance > 128 * 1000 {
+        true => RgbColor(0, 0, 0),
+        false => RgbColor(255, 255, 255),
+    }
+}
+
+fn distance(a: RgbColor, b: RgbColor) -> u32 {
+    let diff = |a: u8, b: u8| (a as i32 - b as i32).unsigned_abs();
+
+    diff(a.0, b.0).pow(2) + diff(a.1, b.1).pow(2) + diff(a.2, b.2).pow(2)
+}
+
+fn nearest_cube_level(value: u8) -> usize {
+    CUBE_LEVELS
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, level)| (**level as i32 - value as i32).abs())
+        .map(|(index, _)| index)
+        .unwrap_or_default()
+}
+
+/// Maps the color to the nearest color of the color cube or grayscale ramp
+/// of the 256 color palette. The first 16 colors are skipped, since their
+/// values depend on the terminal.
+fn nearest_ansi256(rgb: RgbColor) -> Ansi256Color {
+    let (r, g, b) = (
+        nearest_cube_level(rgb.0),
+        nearest_cube_level(rgb.1),
+        nearest_cube_level(rgb.2),
+    );
+    let cube = (16 + 36 * r + 6 * g + b) as u8;
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray = (average.saturating_sub(3) / 10).min(23) as u8 + 232;
+
+    match distance(rgb, ansi256_to_rgb(Ansi256Color(gray)))
+        < distance(rgb, ansi256_to_rgb(Ansi256Color(cube)))
+    {
+        true => Ansi256Color(gray),
+        false => Ansi256Color(cube),
+    }
+}
+
+fn nearest_ansi16(rgb: RgbColor) -> AnsiColor {
+    let index = ANSI_COLORS
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, (r, g, b))| distance(rgb, RgbColor(*r, *g, *b)))
+        .map(|(index, _)| index)
+        .unwrap_or_default();
+
+    Ansi256Color(index as u8)
+        .into_ansi()
+        .unwrap_or(AnsiColor::White)
+}
+
+fn ansi256_to_rgb(color: Ansi256Color) -> RgbColor {
+    match color.0 {
+        index @ 0..=15 => {
+            let (r, g, b) = ANSI_COLORS[index as usize];
+            RgbColor(r, g, b)
+        }
+        index @ 16..=231 => {
+            let index = index - 16;
+
+            RgbColor(
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        index => {
+            let level = 8 + 10 * (index - 232);
+            RgbColor(level, level, level)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(ColorDepth::TrueColor, RgbColor(1, 2, 3).into(), Some(RgbColor(1, 2, 3).into()))]
+    #[case(ColorDepth::Ansi256, RgbColor(255, 0, 0).into(), Some(Ansi256Color(196).into()))]
+    #[case(ColorDepth::Ansi256, RgbColor(128, 128, 130).into(), Some(Ansi256Color(244).into()))]
+    #[case(ColorDepth::Ansi256, AnsiColor::Red.into(), Some(AnsiColor::Red.into()))]
+    #[case(ColorDepth::Ansi16, RgbColor(250, 10, 10).into(), Some(AnsiColor::BrightRed.into()))]
+    #[case(ColorDepth::Ansi16, Ansi256Color(4).into(), Some(AnsiColor::Blue.into()))]
+    #[case(ColorDepth::Ansi16, Ansi256Color(22).into(), Some(AnsiColor::Black.into()))]
+    #[case(ColorDepth::None, AnsiColor::Red.into(), None)]
+    fn test_color_depth_convert(
+        #[case] depth: ColorDepth,
+        #[case] color: Color,
+        #[case] expected: Option<Color>,
+    ) {
+        assert_eq!(depth.convert(Some(color)), expected);
+    }
+
+    #[test]
+    fn test_color_functions() {
+        let color = RgbColor(100, 200, 50);
+
+        assert_eq!(darken(color, 50), RgbColor(50, 100, 25));
+        assert_eq!(lighten(color, 100), RgbColor(255, 255, 255));
+        assert_eq!(mix(color, RgbColor(0, 0, 250), 20), RgbColor(80, 160, 90));
+        assert_eq!(contrast(color), RgbColor(0, 0, 0));
+        assert_eq!(contrast(RgbColor(30, 30, 46)), RgbColor(255, 255, 255));
+        assert_eq!(to_rgb(AnsiColor::Blue.into()), RgbColor(0, 0, 238));
+    }
+}
@@ -1,19 +1,29 @@
 use std::{collections::BTreeMap, str::FromStr, sync::Arc};
 
 use itertools::Itertools;
-use regex::Regex;
 use zellij_tile::prelude::*;
 
 use crate::{
+    action::{ClickAction, parse_mouse_action_key},
     border::{parse_border_config, BorderConfig, BorderPosition},
-    render::FormattedPart,
-    widgets::{command::CommandResult, notification, widget::Widget},
+    color::ColorDepth,
+    render::{
+        ContentNode, FormattedPart, HitMap, TextColors, count_spacers, expand_spacers,
+        measure_text_width, render_parts_hiding, spacer_widths, truncate_text,
+        widget_name_from_key,
+    },
+    widgets::{
+        command::CommandResult,
+        notification,
+        widget::{MouseAction, Widget},
+    },
 };
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Duration, Local};
 
 #[derive(Default, Debug, Clone)]
 pub struct ZellijState {
     pub cols: usize,
+    pub rows: usize,
     pub command_results: BTreeMap<String, CommandResult>,
     pub pipe_results: BTreeMap<String, String>,
     pub mode: ModeInfo,
@@ -23,11 +33,17 @@ pub struct ZellijState {
     pub sessions: Vec<SessionInfo>,
     pub start_time: DateTime<Local>,
     pub incoming_notification: Option<notification::Message>,
-    pub cache_mask: u8,
+    /// Set for instances in tabs, that are not active. They run no commands
+    /// and schedule no renders, until their tab becomes active.
+    pub background: bool,
+    pub hover: Option<Hover>,
+    pub revision: u64,
+    pub changes: BTreeMap<Dependency, u64>,
 }
 
-#[derive(Clone, Debug, Ord, Eq, PartialEq, PartialOrd, Copy)]
+#[derive(Clone, Debug, Default, Ord, Eq, PartialEq, PartialOrd, Copy)]
 pub enum Part {
+    #[default]
     Left,
     Center,
     Right,
@@ -46,37 +62,387 @@ impl FromStr for Part {
     type Err = anyhow::Error;
 }
 
-pub enum UpdateEventMask {
-    Always = 0b10000000,
-    Mode = 0b00000001,
-    Tab = 0b00000011,
-    Command = 0b00000100,
-    Session = 0b00001000,
-    None = 0b00000000,
+/// Placement of the center section, configured with `format_center_mode`.
+#[derive(Clone, Debug, Default, PartialEq, Copy)]
+pub enum CenterMode {
+    /// Centered on the screen, regardless of the other sections.
+    #[default]
+    Screen,
+    /// Centered between the rendered left and right sections.
+    Between,
+    /// Centered on the screen, but nudged away from the left and right
+    /// sections instead of overlapping them.
+    LeftBiased,
 }
 
-pub fn event_mask_from_widget_name(name: &str) -> u8 {
-    match name {
-        "command" => UpdateEventMask::Always as u8,
-        "datetime" => UpdateEventMask::Always as u8,
-        "mode" => UpdateEventMask::Mode as u8,
-        "notifications" => UpdateEventMask::Always as u8,
-        "session" => UpdateEventMask::Mode as u8,
-        "swap_layout" => UpdateEventMask::Tab as u8,
-        "tabs" => UpdateEventMask::Tab as u8,
-        "pipe" => UpdateEventMask::Always as u8,
-        _ => UpdateEventMask::None as u8,
+impl FromStr for CenterMode {
+    fn from_str(mode: &str) -> Result<Self> {
+        match mode {
+            "screen" => Ok(CenterMode::Screen),
+            "between" => Ok(CenterMode::Between),
+            "left-biased" => Ok(CenterMode::LeftBiased),
+            _ => anyhow::bail!("Invalid format_center_mode: {}", mode),
+        }
+    }
+
+    type Err = anyhow::Error;
+}
+
+/// Part of the [`ZellijState`], that the output of a widget depends on.
+/// Commands and pipes are tracked by name, so that e.g. a new result for
+/// `pipe_1` does not render `pipe_2` again.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Dependency {
+    Mode,
+    Tabs,
+    Panes,
+    Sessions,
+    Command(String),
+    Pipe(String),
+    Notifications,
+    Hover,
+    /// Changes whenever a timer fires, see [`Widget::next_update`].
+    Clock,
+}
+
+/// Time in seconds after the last hover event, after which the cursor is
+/// considered to have left the bar. Zellij does not report, when the cursor
+/// leaves the plugin pane.
+const HOVER_TIMEOUT_SECONDS: i64 = 5;
+
+/// Widget under the cursor. It is cleared, once the cursor leaves it or no
+/// hover event has been received for a while.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hover {
+    pub key: String,
+    /// Offset of the cursor into the output of the widget.
+    pub pos: usize,
+    /// Details of the widget, see [`Widget::tooltip`].
+    pub tooltip: Option<String>,
+    pub since: DateTime<Local>,
+    /// Time of the last hover event over the widget.
+    pub seen: DateTime<Local>,
+}
+
+impl Hover {
+    pub fn expires_at(&self) -> DateTime<Local> {
+        self.seen + Duration::seconds(HOVER_TIMEOUT_SECONDS)
+    }
+}
+
+impl ZellijState {
+    /// Records, that the dependency has changed. Cached output, that has been
+    /// rendered before, is rendered again on the next render.
+    pub fn mark_changed(&mut self, dependency: Dependency) {
+        self.revision += 1;
+        self.changes.insert(dependency, self.revision);
+    }
+
+    /// Clears the hover, once it expired. Returns whether it has been cleared.
+    pub fn expire_hover(&mut self) -> bool {
+        if self
+            .hover
+            .as_ref()
+            .is_none_or(|hover| hover.expires_at() > Local::now())
+        {
+            return false;
+        }
+
+        self.hover = None;
+        self.mark_changed(Dependency::Hover);
+
+        true
+    }
+
+    /// Returns whether any of the dependencies changed after the revision.
+    pub fn changed_since(&self, dependencies: &[Dependency], revision: u64) -> bool {
+        dependencies.iter().any(|dependency| {
+            self.changes
+                .get(dependency)
+                .is_some_and(|changed| *changed > revision)
+        })
+    }
+}
+
+/// Format of a section of the bar. Variants like `format_right@<120` replace
+/// the format, while the terminal is narrower than their breakpoint. The
+/// width of the section can be limited with e.g. `format_right_min_width`
+/// and `format_right_max_width`.
+#[derive(Default, Debug)]
+pub struct SectionFormat {
+    pub part: Part,
+    pub parts_config: String,
+    pub parts: Vec<FormattedPart>,
+    pub variants: Vec<(usize, Vec<FormattedPart>)>,
+    pub min_width: Option<usize>,
+    pub max_width: Option<usize>,
+    /// Parts, that have been dropped on the last render to fit the bar.
+    pub hidden: Vec<bool>,
+    /// Space before and after the section, that has been added on the last
+    /// render to reach the minimum width.
+    pub padding: (usize, usize),
+    /// Output of the last render and the width it has been rendered for. It
+    /// is reused, as long as no widget of the section has to be updated.
+    pub last_output: Option<(usize, String)>,
+    /// Widgets within the output of the last render.
+    pub hits: HitMap,
+}
+
+impl SectionFormat {
+    fn new(key: &str, part: Part, config: &BTreeMap<String, String>) -> Self {
+        let parts_config = match config.get(key) {
+            Some(conf) => conf,
+            None => "",
+        };
+
+        let prefix = format!("{key}@");
+        let mut variants: Vec<(usize, Vec<FormattedPart>)> = config
+            .iter()
+            .filter_map(|(variant_key, format)| {
+                let breakpoint = parse_breakpoint(variant_key.strip_prefix(&prefix)?)?;
+
+                Some((breakpoint, parts_from_config(Some(format), config)))
+            })
+            .collect();
+        variants.sort_by_key(|(breakpoint, _)| *breakpoint);
+
+        Self {
+            part,
+            parts_config: parts_config.to_owned(),
+            parts: parts_from_config(Some(&parts_config.to_owned()), config),
+            variants,
+            min_width: config
+                .get(&format!("{key}_min_width"))
+                .and_then(|width| width.parse().ok()),
+            max_width: config
+                .get(&format!("{key}_max_width"))
+                .and_then(|width| width.parse().ok()),
+            hidden: Vec::new(),
+            padding: (0, 0),
+            last_output: None,
+            hits: HitMap::default(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.parts.is_empty() && self.variants.iter().all(|(_, parts)| parts.is_empty())
+    }
+
+    fn render(
+        &mut self,
+        widget_map: &BTreeMap<String, Arc<dyn Widget>>,
+        state: &ZellijState,
+        space: &FormattedPart,
+        indicator: &str,
+    ) -> String {
+        let fallback = TextColors {
+            fg: space.fg,
+            bg: space.bg,
+        };
+
+        let parts = parts_for_width(&mut self.variants, &mut self.parts, state.cols);
+
+        let visible = !self.hidden.contains(&true);
+
+        if visible
+            && parts
+                .iter_mut()
+                .all(|part| part.is_fresh(widget_map, state))
+            && let Some((cols, output)) = &self.last_output
+            && *cols == state.cols
+        {
+            return output.to_owned();
+        }
+
+        let output =
+            render_parts_hiding(parts, widget_map, state, fallback, &self.hidden, indicator);
+        let mut hits = parts_hits(parts, &self.hidden, measure_text_width(indicator));
+
+        let output = self.fit_width(output, space);
+
+        if let Some(max_width) = self.max_width {
+            hits.truncate(max_width);
+        }
+        self.hits = HitMap::default();
+        self.hits.extend(&hits, self.padding.0);
+
+        self.last_output = visible.then(|| (state.cols, output.to_owned()));
+
+        output
+    }
+
+    /// Truncates the output to the maximum width and pads it to the minimum
+    /// width. The padding is added on the side facing the center of the bar.
+    fn fit_width(&mut self, output: String, space: &FormattedPart) -> String {
+        let output = match self.max_width {
+            Some(max_width) if measure_text_width(&output) > max_width => {
+                truncate_text(&output, max_width, "")
+            }
+            _ => output,
+        };
+
+        let padding = self
+            .min_width
+            .unwrap_or_default()
+            .saturating_sub(measure_text_width(&output));
+
+        self.padding = match self.part {
+            Part::Left => (0, padding),
+            Part::Center => (padding / 2, padding - padding / 2),
+            Part::Right => (padding, 0),
+        };
+
+        let pad = |count: usize| match count {
+            0 => String::new(),
+            count => space.format_string(&" ".repeat(count)),
+        };
+
+        format!("{}{}{}", pad(self.padding.0), output, pad(self.padding.1))
+    }
+}
+
+/// Returns the variant with the smallest breakpoint above the width, or the
+/// format itself if there is none. It takes the fields of the
+/// [`SectionFormat`], so that the other fields can be borrowed alongside.
+fn parts_for_width<'a>(
+    variants: &'a mut [(usize, Vec<FormattedPart>)],
+    format: &'a mut [FormattedPart],
+    cols: usize,
+) -> &'a mut [FormattedPart] {
+    match variants
+        .iter_mut()
+        .find(|(breakpoint, _)| cols < *breakpoint)
+    {
+        Some((_, parts)) => parts,
+        None => format,
+    }
+}
+
+/// A single line of the bar with its own left, center and right format. The
+/// first row is configured with `format_left`, `format_center` and
+/// `format_right`, further rows append their number, e.g. `format_left_2`.
+/// A single `format` may be used instead of `format_left`, which is laid out
+/// with `{spacer}` tokens.
+#[derive(Default, Debug)]
+pub struct BarRow {
+    pub left: SectionFormat,
+    pub center: SectionFormat,
+    pub right: SectionFormat,
+    /// Widgets within the row, as it has been rendered last.
+    pub hits: HitMap,
+}
+
+impl BarRow {
+    fn new(row: usize, config: &BTreeMap<String, String>) -> Self {
+        let suffix = match row {
+            1 => "".to_owned(),
+            row => format!("_{row}"),
+        };
+
+        let left = match config.contains_key(&format!("format{suffix}")) {
+            true => format!("format{suffix}"),
+            false => format!("format_left{suffix}"),
+        };
+
+        Self {
+            left: SectionFormat::new(&left, Part::Left, config),
+            center: SectionFormat::new(&format!("format_center{suffix}"), Part::Center, config),
+            right: SectionFormat::new(&format!("format_right{suffix}"), Part::Right, config),
+            hits: HitMap::default(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.left.is_empty() && self.center.is_empty() && self.right.is_empty()
+    }
+
+    fn render_sections(
+        &mut self,
+        widget_map: &BTreeMap<String, Arc<dyn Widget>>,
+        state: &ZellijState,
+        space: &FormattedPart,
+        indicator: &str,
+    ) -> (String, String, String) {
+        (
+            self.left.render(widget_map, state, space, indicator),
+            self.center.render(widget_map, state, space, indicator),
+            self.right.render(widget_map, state, space, indicator),
+        )
+    }
+
+    /// Hides the parts with the lowest priority one by one, until the row fits
+    /// into `cols`. Returns whether any part has been hidden.
+    fn drop_parts(
+        &mut self,
+        widget_map: &BTreeMap<String, Arc<dyn Widget>>,
+        state: &ZellijState,
+        drop_order: &[String],
+        indicator_width: usize,
+    ) -> bool {
+        let mut sections = [&mut self.left, &mut self.center, &mut self.right];
+        let mut widths: Vec<Vec<usize>> = Vec::new();
+        let mut ranks: Vec<Vec<Option<(usize, usize)>>> = Vec::new();
+
+        for section in sections.iter_mut() {
+            let parts = parts_for_width(&mut section.variants, &mut section.parts, state.cols);
+
+            widths.push(
+                parts
+                    .iter_mut()
+                    .map(|part| {
+                        measure_text_width(&part.format_string_with_widgets(widget_map, state))
+                    })
+                    .collect(),
+            );
+            ranks.push(
+                parts
+                    .iter()
+                    .map(|part| drop_rank(part, drop_order))
+                    .collect(),
+            );
+            section.hidden = vec![false; parts.len()];
+        }
+
+        let mut dropped = false;
+
+        loop {
+            let width: usize = sections
+                .iter()
+                .zip(&widths)
+                .map(|(section, widths)| hidden_width(widths, &section.hidden, indicator_width))
+                .sum();
+
+            if width <= state.cols {
+                break;
+            }
+
+            let candidate = ranks
+                .iter()
+                .enumerate()
+                .flat_map(|(s, ranks)| {
+                    ranks
+                        .iter()
+                        .enumerate()
+                        .filter_map(move |(i, rank)| Some((*rank.as_ref()?, s, i)))
+                })
+                .filter(|(_, s, i)| !sections[*s].hidden[*i] && widths[*s][*i] > 0)
+                .max_by_key(|(rank, _, _)| *rank);
+
+            let Some((_, s, i)) = candidate else {
+                break;
+            };
+
+            sections[s].hidden[i] = true;
+            dropped = true;
+        }
+
+        dropped
     }
 }
 
 #[derive(Default, Debug)]
 pub struct ModuleConfig {
-    pub left_parts_config: String,
-    pub left_parts: Vec<FormattedPart>,
-    pub center_parts_config: String,
-    pub center_parts: Vec<FormattedPart>,
-    pub right_parts_config: String,
-    pub right_parts: Vec<FormattedPart>,
+    pub rows: Vec<BarRow>,
     pub format_space: FormattedPart,
     pub hide_frame_for_single_pane: bool,
     pub hide_frame_except_for_search: bool,
@@ -84,7 +450,15 @@ pub struct ModuleConfig {
     pub hide_frame_except_for_scroll: bool,
     pub border: BorderConfig,
     pub format_precedence: Vec<Part>,
+    pub format_center_mode: CenterMode,
+    pub format_drop_order: Vec<String>,
+    pub format_overflow: Vec<FormattedPart>,
     pub hide_on_overlength: bool,
+    /// Last known position of the cursor, since scroll events carry none.
+    pub mouse_position: Option<(isize, usize)>,
+    /// Actions configured for a widget, e.g. `mode_clickaction`. They replace
+    /// the click handling of the widget.
+    pub click_actions: BTreeMap<(String, MouseAction), ClickAction>,
 }
 
 impl ModuleConfig {
@@ -112,21 +486,6 @@ impl ModuleConfig {
             None => false,
         };
 
-        let left_parts_config = match config.get("format_left") {
-            Some(conf) => conf,
-            None => "",
-        };
-
-        let right_parts_config = match config.get("format_right") {
-            Some(conf) => conf,
-            None => "",
-        };
-
-        let center_parts_config = match config.get("format_center") {
-            Some(conf) => conf,
-            None => "",
-        };
-
         let format_precedence = match config.get("format_precedence") {
             Some(conf) => {
                 let prec = conf
@@ -144,20 +503,55 @@ impl ModuleConfig {
             None => vec![Part::Left, Part::Center, Part::Right],
         };
 
+        let format_center_mode = match config.get("format_center_mode") {
+            Some(mode) => CenterMode::from_str(mode)?,
+            None => CenterMode::default(),
+        };
+
+        let format_drop_order = match config.get("format_drop_order") {
+            Some(order) => order
+                .split(',')
+                .map(|name| name.trim().to_owned())
+                .filter(|name| !name.is_empty())
+                .collect(),
+            None => Vec::new(),
+        };
+
         let hide_on_overlength = match config.get("format_hide_on_overlength") {
             Some(opt) => opt == "true",
             None => false,
         };
 
+        if let Some(depth) = config.get("color_depth")
+            && ColorDepth::parse(depth).is_none()
+        {
+            anyhow::bail!("Invalid color_depth: {}", depth);
+        }
+
+        for key in config.keys().filter(|key| key.starts_with("format")) {
+            if let Some((_, variant)) = key.split_once('@')
+                && parse_breakpoint(variant).is_none()
+            {
+                anyhow::bail!("Invalid {}: expected a breakpoint like @<120", key);
+            }
+        }
+
+        for (key, width) in config.iter().filter(|(key, _)| is_width_key(key)) {
+            if width.parse::<usize>().is_err() {
+                anyhow::bail!("Invalid {}: expected a number", key);
+            }
+        }
+
+        validate_formats(config)?;
+
+        let click_actions = parse_click_actions(config)?;
+
         let border_config = parse_border_config(config).unwrap_or_default();
 
         Ok(Self {
-            left_parts_config: left_parts_config.to_owned(),
-            left_parts: parts_from_config(Some(&left_parts_config.to_owned()), config),
-            center_parts_config: center_parts_config.to_owned(),
-            center_parts: parts_from_config(Some(&center_parts_config.to_owned()), config),
-            right_parts_config: right_parts_config.to_owned(),
-            right_parts: parts_from_config(Some(&right_parts_config.to_owned()), config),
+            rows: (1..=row_count(config))
+                .map(|row| BarRow::new(row, config))
+                .collect(),
             format_space: FormattedPart::from_format_string(format_space_config, config),
             hide_frame_for_single_pane,
             hide_frame_except_for_search,
@@ -165,258 +559,308 @@ impl ModuleConfig {
             hide_frame_except_for_scroll,
             border: border_config,
             format_precedence,
+            format_center_mode,
+            format_drop_order,
+            format_overflow: parts_from_config(config.get("format_overflow"), config),
             hide_on_overlength,
+            mouse_position: None,
+            click_actions,
         })
     }
 
+    /// Takes over the state of the configuration, that is replaced by this
+    /// one, e.g. when theme colors change. Cached output is dropped, since it
+    /// has been rendered with the replaced formats.
+    pub fn restore_runtime_state(&mut self, previous: &ModuleConfig) {
+        self.mouse_position = previous.mouse_position;
+
+        for (row, previous_row) in self.rows.iter_mut().zip(&previous.rows) {
+            row.hits = previous_row.hits.clone();
+        }
+    }
+
+    /// Passes clicks and scrolling to the widget or click region under the
+    /// cursor, as it has been rendered last. Returns whether the bar should be
+    /// rendered, since the hovered widget changed.
     pub fn handle_mouse_action(
         &mut self,
-        state: ZellijState,
+        state: &mut ZellijState,
         mouse: Mouse,
-        widget_map: BTreeMap<String, Arc<dyn Widget>>,
-    ) {
-        let click_pos = match mouse {
-            Mouse::ScrollUp(_) => return,
-            Mouse::ScrollDown(_) => return,
-            Mouse::LeftClick(_, y) => y,
-            Mouse::RightClick(_, y) => y,
-            Mouse::Hold(_, y) => y,
-            Mouse::Release(_, y) => y,
-            Mouse::Hover(_, _) => return,
-        };
-
-        let output_left = self.left_parts.iter_mut().fold("".to_owned(), |acc, part| {
-            format!(
-                "{}{}",
-                acc,
-                part.format_string_with_widgets(&widget_map, &state)
-            )
-        });
-
-        let output_center = self
-            .center_parts
-            .iter_mut()
-            .fold("".to_owned(), |acc, part| {
-                format!(
-                    "{}{}",
-                    acc,
-                    part.format_string_with_widgets(&widget_map, &state)
-                )
-            });
-
-        let output_right = self
-            .right_parts
-            .iter_mut()
-            .fold("".to_owned(), |acc, part| {
-                format!(
-                    "{}{}",
-                    acc,
-                    part.format_string_with_widgets(&widget_map, &state)
-                )
-            });
-
-        let (output_left, output_center, output_right) = match self.hide_on_overlength {
-            true => self.trim_output(&output_left, &output_center, &output_right, state.cols),
-            false => (output_left, output_center, output_right),
+        widget_map: &BTreeMap<String, Arc<dyn Widget>>,
+    ) -> bool {
+        let (action, line, click_pos) = match mouse {
+            Mouse::LeftClick(line, col) => (MouseAction::LeftClick, line, col),
+            Mouse::RightClick(line, col) => (MouseAction::RightClick, line, col),
+            Mouse::ScrollUp(_) | Mouse::ScrollDown(_) => {
+                let Some((line, col)) = self.mouse_position else {
+                    return false;
+                };
+
+                match mouse {
+                    Mouse::ScrollUp(_) => (MouseAction::ScrollUp, line, col),
+                    _ => (MouseAction::ScrollDown, line, col),
+                }
+            }
+            Mouse::Hover(line, col) => {
+                self.mouse_position = Some((line, col));
+                return self.update_hover(state, line, col, widget_map);
+            }
+            Mouse::Hold(_, _) | Mouse::Release(_, _) => return false,
         };
 
-        let mut offset = console::measure_text_width(&output_left);
+        self.mouse_position = Some((line, click_pos));
 
-        self.process_widget_click(click_pos, &self.left_parts, &widget_map, &state, 0);
+        // widgets take precedence over the click regions around them
+        let Some((key, pos)) = self.widget_at(line, click_pos, state) else {
+            if action == MouseAction::LeftClick
+                && let Some(index) = self.row_at_line(line, state.rows)
+                && let Some(click_action) = self.rows[index].hits.find_region(click_pos)
+            {
+                click_action.run(state);
+            }
 
-        if click_pos <= offset {
-            return;
-        }
+            return false;
+        };
 
-        if !output_center.is_empty() {
-            tracing::debug!("widgetclick center");
-            offset += console::measure_text_width(&self.get_spacer_left(
-                &output_left,
-                &output_center,
-                state.cols,
-            ));
+        if let Some(click_action) = self.click_actions.get(&(key.to_owned(), action)) {
+            click_action.run(state);
+        } else if let Some(widget) = widget_map.get(widget_name_from_key(key)) {
+            widget.process_click(key, state, pos, action);
+        }
 
-            offset += self.process_widget_click(
-                click_pos,
-                &self.center_parts,
-                &widget_map,
-                &state,
-                offset,
-            );
+        false
+    }
 
-            if click_pos <= offset {
-                return;
-            }
+    /// Returns the key of the widget at the position and the offset into its
+    /// output.
+    fn widget_at(&self, line: isize, col: usize, state: &ZellijState) -> Option<(&str, usize)> {
+        let index = self.row_at_line(line, state.rows)?;
 
-            offset += console::measure_text_width(&self.get_spacer_right(
-                &output_right,
-                &output_center,
-                state.cols,
-            ));
-        } else {
-            offset += console::measure_text_width(&self.get_spacer(
-                &output_left,
-                &output_right,
-                state.cols,
-            ));
-        }
-
-        self.process_widget_click(click_pos, &self.right_parts, &widget_map, &state, offset);
+        self.rows[index].hits.find(col)
     }
 
-    fn process_widget_click(
+    /// Updates the widget under the cursor. Returns whether it changed.
+    fn update_hover(
         &self,
-        click_pos: usize,
-        widgets: &[FormattedPart],
+        state: &mut ZellijState,
+        line: isize,
+        col: usize,
         widget_map: &BTreeMap<String, Arc<dyn Widget>>,
-        state: &ZellijState,
-        offset: usize,
-    ) -> usize {
-        let widget_string = widgets.iter().fold(String::new(), |a, b| a + &b.content);
-
-        let mut rendered_output = widget_string.clone();
-
-        let tokens: Vec<String> = widget_map.keys().map(|k| k.to_owned()).collect();
+    ) -> bool {
+        let now = Local::now();
+        let hover = self.widget_at(line, col, state).and_then(|(key, pos)| {
+            let widget = widget_map.get(widget_name_from_key(key))?;
+
+            Some(Hover {
+                key: key.to_owned(),
+                pos,
+                tooltip: widget.tooltip(key, state, pos),
+                since: now,
+                seen: now,
+            })
+        });
 
-        let widgets_regex = Regex::new("(\\{[a-z_0-9]+\\})").unwrap();
-        for widget in widgets_regex.captures_iter(widget_string.as_str()) {
-            let match_name = widget.get(0).unwrap().as_str();
-            let widget_key = match_name.trim_matches(|c| c == '{' || c == '}');
-            let mut widget_key_name = widget_key;
+        let hover = match (&state.hover, hover) {
+            (Some(current), Some(hover)) if current.key == hover.key => {
+                if current.pos == hover.pos {
+                    // the cursor is still there, so the hover does not expire
+                    if let Some(current) = state.hover.as_mut() {
+                        current.seen = now;
+                    }
 
-            if widget_key.starts_with("command_") {
-                widget_key_name = "command";
-            }
+                    return false;
+                }
 
-            if widget_key.starts_with("pipe_") {
-                widget_key_name = "pipe";
+                // the tooltip is shown for its whole duration, while the
+                // cursor moves within the widget
+                match current.tooltip == hover.tooltip {
+                    true => Hover {
+                        since: current.since,
+                        ..hover
+                    },
+                    false => hover,
+                }
             }
+            (None, None) => return false,
+            (_, hover) => match hover {
+                Some(hover) => hover,
+                None => {
+                    state.hover = None;
+                    state.mark_changed(Dependency::Hover);
+
+                    return true;
+                }
+            },
+        };
 
-            if !tokens.contains(&widget_key_name.to_owned()) {
-                continue;
-            }
+        state.hover = Some(hover);
+        state.mark_changed(Dependency::Hover);
 
-            let wid = match widget_map.get(widget_key_name) {
-                Some(wid) => wid,
-                None => continue,
-            };
+        true
+    }
 
-            let pos = match rendered_output.find(match_name) {
-                Some(_pos) => {
-                    let pref = rendered_output.split(match_name).collect::<Vec<&str>>()[0];
-                    console::measure_text_width(pref)
-                }
-                None => continue,
-            };
+    /// Maps the line of a mouse event to the rendered row, skipping the top
+    /// border.
+    fn row_at_line(&self, line: isize, rows: usize) -> Option<usize> {
+        let border_top = self.border.enabled && self.border.position == BorderPosition::Top;
+        let index = usize::try_from(line - border_top as isize).ok()?;
 
-            let wid_res = wid.process(widget_key, state);
-            rendered_output = rendered_output.replace(match_name, &wid_res);
+        match index < self.visible_rows(rows) {
+            true => Some(index),
+            false => None,
+        }
+    }
 
-            if click_pos < pos + offset
-                || click_pos > pos + offset + console::measure_text_width(&wid_res)
-            {
-                continue;
-            }
+    /// Returns the number of rows, that fit into the plugin pane next to the
+    /// border. Zellij reports 0 rows before the first render, in which case
+    /// all rows are rendered.
+    fn visible_rows(&self, rows: usize) -> usize {
+        let available = rows.saturating_sub(self.border.enabled as usize);
 
-            wid.process_click(widget_key, state, click_pos - (pos + offset));
+        match rows {
+            0 => self.rows.len(),
+            _ => self.rows.len().min(available.max(1)),
         }
-
-        console::measure_text_width(&rendered_output)
     }
 
     pub fn render_bar(
         &mut self,
-        state: ZellijState,
-        widget_map: BTreeMap<String, Arc<dyn Widget>>,
+        state: &ZellijState,
+        widget_map: &BTreeMap<String, Arc<dyn Widget>>,
     ) -> String {
-        if self.left_parts.is_empty() && self.center_parts.is_empty() && self.right_parts.is_empty()
-        {
+        if self.rows.iter().all(BarRow::is_empty) {
             return "No configuration found. See https://github.com/dj95/zjstatus/wiki/3-%E2%80%90-Configuration for more info".to_string();
         }
 
-        let output_left = self.left_parts.iter_mut().fold("".to_owned(), |acc, part| {
-            format!(
-                "{acc}{}",
-                part.format_string_with_widgets(&widget_map, &state)
+        let mut lines: Vec<String> = (0..self.visible_rows(state.rows))
+            .map(|index| self.render_row(index, widget_map, state))
+            .collect();
+
+        if self.border.enabled {
+            match self.border.position {
+                BorderPosition::Top => lines.insert(0, self.border.draw(state.cols)),
+                BorderPosition::Bottom => lines.push(self.border.draw(state.cols)),
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    fn render_row(
+        &mut self,
+        index: usize,
+        widget_map: &BTreeMap<String, Arc<dyn Widget>>,
+        state: &ZellijState,
+    ) -> String {
+        let (output_left, output_center, output_right) =
+            self.render_row_sections(index, widget_map, state);
+
+        let outputs = [&output_left, &output_center, &output_right];
+        let widths = outputs.map(|output| measure_text_width(output));
+        let spacers: usize = outputs.iter().map(|output| count_spacers(output)).sum();
+        let free = state.cols.saturating_sub(widths.iter().sum());
+
+        let (output, offsets) = if spacers > 0 {
+            let output = format!("{output_left}{output_center}{output_right}");
+
+            (
+                expand_spacers(&output, free),
+                [0, widths[0], widths[0] + widths[1]],
             )
-        });
+        } else if !output_center.is_empty() {
+            let (spacer_left, spacer_right) =
+                self.get_center_spacers(&output_left, &output_center, &output_right, state.cols);
+            let center_offset = widths[0] + measure_text_width(&spacer_left);
 
-        let output_center = self
-            .center_parts
-            .iter_mut()
-            .fold("".to_owned(), |acc, part| {
+            (
                 format!(
-                    "{acc}{}",
-                    part.format_string_with_widgets(&widget_map, &state)
-                )
-            });
-
-        let output_right = self
-            .right_parts
-            .iter_mut()
-            .fold("".to_owned(), |acc, part| {
-                format!(
-                    "{acc}{}",
-                    part.format_string_with_widgets(&widget_map, &state)
-                )
-            });
+                    "{}{}{}{}{}",
+                    output_left, spacer_left, output_center, spacer_right, output_right,
+                ),
+                [
+                    0,
+                    center_offset,
+                    center_offset + widths[1] + measure_text_width(&spacer_right),
+                ],
+            )
+        } else {
+            let spacer = self.get_spacer(&output_left, &output_right, state.cols);
+            let right_offset = widths[0] + measure_text_width(&spacer);
 
-        let (output_left, output_center, output_right) = match self.hide_on_overlength {
-            true => self.trim_output(&output_left, &output_center, &output_right, state.cols),
-            false => (output_left, output_center, output_right),
+            (
+                format!("{}{}{}", output_left, spacer, output_right),
+                [0, widths[0], right_offset],
+            )
         };
 
-        if self.border.enabled {
-            let mut border_top = "".to_owned();
-            if self.border.enabled && self.border.position == BorderPosition::Top {
-                border_top = format!("{}\n", self.border.draw(state.cols));
-            }
+        let row = &mut self.rows[index];
+        let mut hits = HitMap::default();
 
-            let mut border_bottom = "".to_owned();
-            if self.border.enabled && self.border.position == BorderPosition::Bottom {
-                border_bottom = format!("\n{}", self.border.draw(state.cols));
+        for ((section, output), offset) in [&row.left, &row.center, &row.right]
+            .into_iter()
+            .zip(outputs)
+            .zip(offsets)
+        {
+            // sections, that have been trimmed to fit the bar, are not shown
+            if !output.is_empty() {
+                hits.extend(&section.hits, offset);
             }
+        }
 
-            if !output_center.is_empty() {
-                return format!(
-                    "{}{}{}{}{}{}{}",
-                    border_top,
-                    output_left,
-                    self.get_spacer_left(&output_left, &output_center, state.cols),
-                    output_center,
-                    self.get_spacer_right(&output_right, &output_center, state.cols),
-                    output_right,
-                    border_bottom,
-                );
-            }
+        if spacers > 0 {
+            hits.expand_spacers(&spacer_widths(spacers, free));
+        }
+        row.hits = hits;
 
-            return format!(
-                "{}{}{}{}{}",
-                border_top,
-                output_left,
-                self.get_spacer(&output_left, &output_right, state.cols),
-                output_right,
-                border_bottom,
-            );
+        output
+    }
+
+    fn render_row_sections(
+        &mut self,
+        index: usize,
+        widget_map: &BTreeMap<String, Arc<dyn Widget>>,
+        state: &ZellijState,
+    ) -> (String, String, String) {
+        let indicator = self.overflow_indicator();
+
+        let row = &mut self.rows[index];
+        for section in [&mut row.left, &mut row.center, &mut row.right] {
+            section.hidden.clear();
         }
 
-        if !output_center.is_empty() {
-            return format!(
-                "{}{}{}{}{}",
-                output_left,
-                self.get_spacer_left(&output_left, &output_center, state.cols),
-                output_center,
-                self.get_spacer_right(&output_right, &output_center, state.cols),
-                output_right,
-            );
+        let mut sections = row.render_sections(widget_map, state, &self.format_space, &indicator);
+
+        let width = measure_text_width(&sections.0)
+            + measure_text_width(&sections.1)
+            + measure_text_width(&sections.2);
+
+        // zellij sometimes reports 0 cols on tab creation, which should not
+        // drop every widget
+        if state.cols > 0
+            && width > state.cols
+            && row.drop_parts(
+                widget_map,
+                state,
+                &self.format_drop_order,
+                measure_text_width(&indicator),
+            )
+        {
+            sections = row.render_sections(widget_map, state, &self.format_space, &indicator);
         }
 
-        format!(
-            "{}{}{}",
-            output_left,
-            self.get_spacer(&output_left, &output_right, state.cols),
-            output_right,
-        )
+        let (output_left, output_center, output_right) = sections;
+
+        match self.hide_on_overlength {
+            true => self.trim_output(&output_left, &output_center, &output_right, state.cols),
+            false => (output_left, output_center, output_right),
+        }
+    }
+
+    fn overflow_indicator(&self) -> String {
+        self.format_overflow
+            .iter()
+            .map(|part| part.format_string(&part.content))
+            .collect()
     }
 
     fn trim_output(
@@ -446,12 +890,15 @@ impl ModuleConfig {
             let part_a = output.get(a).unwrap();
             let part_b = output.get(b).unwrap();
 
-            let a_count = console::measure_text_width(part_a);
-            let b_count = console::measure_text_width(part_b);
+            let a_count = measure_text_width(part_a);
+            let b_count = measure_text_width(part_b);
+            let total_count: usize = output.values().map(|part| measure_text_width(part)).sum();
 
             let overlap = match (a, b) {
                 (Part::Left, Part::Right) => a_count + b_count > cols,
                 (Part::Right, Part::Left) => a_count + b_count > cols,
+                // the center is moved aside instead, as long as the sections fit
+                _ if self.format_center_mode != CenterMode::Screen => total_count > cols,
                 (Part::Left, Part::Center) => a_count > center_pos - (b_count / 2),
                 (Part::Center, Part::Left) => b_count > center_pos - (a_count / 2),
                 (Part::Right, Part::Center) => a_count > center_pos - (b_count / 2),
@@ -467,10 +914,48 @@ impl ModuleConfig {
         output.values().cloned().collect_tuple().unwrap()
     }
 
+    /// Returns the spacers around the center section according to the
+    /// `format_center_mode`.
+    fn get_center_spacers(
+        &self,
+        output_left: &str,
+        output_center: &str,
+        output_right: &str,
+        cols: usize,
+    ) -> (String, String) {
+        let left_count = measure_text_width(output_left);
+        let center_count = measure_text_width(output_center);
+        let right_count = measure_text_width(output_right);
+
+        // the parts may not fit, in which case there is no space to distribute
+        let free = cols.saturating_sub(left_count + center_count + right_count);
+
+        let space_left = match self.format_center_mode {
+            CenterMode::Screen => {
+                return (
+                    self.get_spacer_left(output_left, output_center, cols),
+                    self.get_spacer_right(output_right, output_center, cols),
+                );
+            }
+            CenterMode::Between => free / 2,
+            CenterMode::LeftBiased => {
+                let center_start = (cols / 2).saturating_sub(center_count / 2);
+
+                center_start.saturating_sub(left_count).min(free)
+            }
+        };
+
+        (
+            self.format_space.format_string(&" ".repeat(space_left)),
+            self.format_space
+                .format_string(&" ".repeat(free - space_left)),
+        )
+    }
+
     #[tracing::instrument(skip_all)]
     fn get_spacer_left(&self, output_left: &str, output_center: &str, cols: usize) -> String {
-        let text_count = console::measure_text_width(output_left)
-            + (console::measure_text_width(output_center) as f32 / 2.0).floor() as usize;
+        let text_count = measure_text_width(output_left)
+            + (measure_text_width(output_center) as f32 / 2.0).floor() as usize;
 
         let center_pos = (cols as f32 / 2.0).floor() as usize;
 
@@ -484,8 +969,8 @@ impl ModuleConfig {
 
     #[tracing::instrument(skip_all)]
     fn get_spacer_right(&self, output_right: &str, output_center: &str, cols: usize) -> String {
-        let text_count = console::measure_text_width(output_right)
-            + (console::measure_text_width(output_center) as f32 / 2.0).ceil() as usize;
+        let text_count = measure_text_width(output_right)
+            + (measure_text_width(output_center) as f32 / 2.0).ceil() as usize;
 
         let center_pos = (cols as f32 / 2.0).ceil() as usize;
 
@@ -498,8 +983,7 @@ impl ModuleConfig {
     }
 
     fn get_spacer(&self, output_left: &str, output_right: &str, cols: usize) -> String {
-        let text_count =
-            console::measure_text_width(output_left) + console::measure_text_width(output_right);
+        let text_count = measure_text_width(output_left) + measure_text_width(output_right);
 
         // verify we are able to count the difference, since zellij sometimes drops a col
         // count of 0 on tab creation
@@ -509,6 +993,86 @@ impl ModuleConfig {
     }
 }
 
+/// Returns the number of configured rows, i.e. the highest row number of
+/// any `format_left_<row>`, `format_center_<row>` or `format_right_<row>`.
+fn row_count(config: &BTreeMap<String, String>) -> usize {
+    config
+        .keys()
+        .filter_map(|key| {
+            let key = key.split_once('@').map_or(key.as_str(), |(key, _)| key);
+            let row = key
+                .strip_prefix("format_left_")
+                .or_else(|| key.strip_prefix("format_center_"))
+                .or_else(|| key.strip_prefix("format_right_"))
+                .or_else(|| key.strip_prefix("format_"))?;
+
+            row.parse::<usize>().ok()
+        })
+        .max()
+        .unwrap_or(1)
+        .max(1)
+}
+
+/// Returns the rank in which a part is dropped on overlength, the part with
+/// the highest rank is dropped first. Widgets in `format_drop_order` are
+/// dropped before widgets with a priority like `{command_k8s:p3}`. Parts
+/// without either are never dropped.
+fn drop_rank(part: &FormattedPart, drop_order: &[String]) -> Option<(usize, usize)> {
+    part.nodes
+        .iter()
+        .filter_map(|node| match node {
+            ContentNode::Placeholder(placeholder) => Some(placeholder),
+            ContentNode::Text(_) | ContentNode::Spacer(_) => None,
+        })
+        .filter_map(|placeholder| {
+            match drop_order.iter().position(|name| *name == placeholder.name) {
+                Some(position) => Some((1, drop_order.len() - position)),
+                None => placeholder.priority.map(|priority| (0, priority)),
+            }
+        })
+        .max()
+}
+
+/// Collects the hits of the rendered parts. Every run of hidden parts is
+/// replaced by the overflow indicator.
+fn parts_hits(parts: &[FormattedPart], hidden: &[bool], indicator_width: usize) -> HitMap {
+    let mut hits = HitMap::default();
+    let mut offset = 0;
+
+    for (index, part) in parts.iter().enumerate() {
+        if hidden.get(index).copied().unwrap_or(false) {
+            if index == 0 || !hidden[index - 1] {
+                offset += indicator_width;
+            }
+            continue;
+        }
+
+        hits.extend(&part.hits, offset);
+        offset += measure_text_width(&part.cached_content);
+    }
+
+    hits
+}
+
+/// Returns the width of a section, where each run of hidden parts is
+/// replaced by the overflow indicator.
+fn hidden_width(widths: &[usize], hidden: &[bool], indicator_width: usize) -> usize {
+    widths
+        .iter()
+        .enumerate()
+        .map(|(index, width)| match hidden[index] {
+            true if index == 0 || !hidden[index - 1] => indicator_width,
+            true => 0,
+            false => *width,
+        })
+        .sum()
+}
+
+/// Parses the breakpoint of a format variant, e.g. `<120`.
+fn parse_breakpoint(variant: &str) -> Option<usize> {
+    variant.strip_prefix('<')?.parse::<usize>().ok()
+}
+
 fn parts_from_config(
     format: Option<&String>,
     config: &BTreeMap<String, String>,
@@ -516,19 +1080,740 @@ fn parts_from_config(
     match format {
         Some(format) => match format.is_empty() {
             true => vec![],
-            false => format
-                .split("#[")
-                .map(|s| FormattedPart::from_format_string(s, config))
-                .collect(),
+            false => FormattedPart::multiple_from_format_string(format, config),
         },
         None => vec![],
     }
 }
 
+/// Parses every format string of the configuration in order to report
+/// syntax errors on load instead of rendering a broken bar.
+fn validate_formats(config: &BTreeMap<String, String>) -> anyhow::Result<()> {
+    for (key, format) in config.iter().filter(|(key, _)| is_format_key(key)) {
+        if let Err(e) = FormattedPart::parse(format, config) {
+            anyhow::bail!("Invalid {}: {}", key, e);
+        }
+    }
+
+    for (key, style) in config.iter().filter(|(key, _)| key.starts_with("style_")) {
+        if let Err(e) = FormattedPart::parse(&format!("#[{style}]"), config) {
+            anyhow::bail!("Invalid {}: {}", key, e.message);
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses the actions for mouse events over widgets. Actions of commands
+/// are run by the command widget, so they are only validated.
+fn parse_click_actions(
+    config: &BTreeMap<String, String>,
+) -> anyhow::Result<BTreeMap<(String, MouseAction), ClickAction>> {
+    let mut click_actions = BTreeMap::new();
+
+    for (key, value) in config {
+        if key.ends_with("_clickaction_middle") {
+            anyhow::bail!(
+                "Invalid {}: middle clicks are not reported to plugins by zellij",
+                key
+            );
+        }
+
+        let Some((name, action)) = parse_mouse_action_key(key) else {
+            continue;
+        };
+
+        let click_action = match value.parse::<ClickAction>() {
+            Ok(click_action) => click_action,
+            Err(e) => anyhow::bail!("Invalid {}: {}", key, e),
+        };
+
+        if !name.starts_with("command_") {
+            click_actions.insert((name.to_owned(), action), click_action);
+        }
+    }
+
+    Ok(click_actions)
+}
+
+/// Returns whether the key configures the width of a section, e.g.
+/// `format_left_min_width`.
+fn is_width_key(key: &str) -> bool {
+    key.starts_with("format") && (key.ends_with("_min_width") || key.ends_with("_max_width"))
+}
+
+fn is_format_key(key: &str) -> bool {
+    let key = match key.split_once('@') {
+        Some((key, _)) if key.starts_with("format") => key,
+        _ => key,
+    };
+
+    if is_width_key(key) {
+        return false;
+    }
+
+    match key {
+        "format" | "format_left" | "format_center" | "format_right" | "format_space" => true,
+        "format_overflow" => true,
+        "border_format" | "datetime" | "swap_layout_format" => true,
+        "tab_rename" | "tab_separator" => true,
+        "tooltip_format" => true,
+        "mode_default_to_mode" => false,
+        _ => {
+            key.strip_prefix("format_")
+                .is_some_and(|row| row.parse::<usize>().is_ok())
+                || key.starts_with("format_left_")
+                || key.starts_with("format_center_")
+                || key.starts_with("format_right_")
+                || key.starts_with("tab_normal")
+                || key.starts_with("tab_active")
+                || key.starts_with("tab_truncate_")
+                || key.starts_with("mode_")
+                || key.starts_with("notification_format_")
+                || ((key.starts_with("command_") || key.starts_with("pipe_"))
+                    && key.ends_with("_format"))
+                || (key.starts_with("command_") && key.ends_with("_format_hover"))
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{
+        action::ZellijAction,
+        widgets::{
+            command::{CommandResult, CommandWidget, TIMESTAMP_FORMAT},
+            pipe::PipeWidget,
+            spinner::SpinnerWidget,
+        },
+    };
     use anstyle::{Effects, RgbColor};
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_render_bar_with_multiple_rows() {
+        let config = BTreeMap::from([
+            ("format_left".to_owned(), "a".to_owned()),
+            ("format_right_3".to_owned(), "c".to_owned()),
+            ("border_enabled".to_owned(), "true".to_owned()),
+            ("border_char".to_owned(), "-".to_owned()),
+        ]);
+
+        let mut module_config = ModuleConfig::new(&config).unwrap();
+        assert_eq!(module_config.rows.len(), 3);
+
+        let state = ZellijState {
+            cols: 3,
+            rows: 4,
+            ..Default::default()
+        };
+        let output = module_config.render_bar(&state, &BTreeMap::new());
+        assert_eq!(console::strip_ansi_codes(&output), "---\na  \n   \n  c");
+
+        assert_eq!(module_config.row_at_line(0, 4), None);
+        assert_eq!(module_config.row_at_line(1, 4), Some(0));
+        assert_eq!(module_config.row_at_line(3, 4), Some(2));
+        assert_eq!(module_config.row_at_line(3, 3), None);
+
+        let state = ZellijState {
+            cols: 3,
+            rows: 2,
+            ..Default::default()
+        };
+        let output = module_config.render_bar(&state, &BTreeMap::new());
+        assert_eq!(console::strip_ansi_codes(&output), "---\na  ");
+    }
+
+    #[test]
+    fn test_render_bar_with_breakpoints() {
+        let config = BTreeMap::from([
+            ("format_left".to_owned(), "left".to_owned()),
+            ("format_right".to_owned(), "wide".to_owned()),
+            ("format_right@<12".to_owned(), "mid".to_owned()),
+            ("format_right@<8".to_owned(), "".to_owned()),
+        ]);
+
+        let mut module_config = ModuleConfig::new(&config).unwrap();
+
+        let mut render = |cols: usize| {
+            let state = ZellijState {
+                cols,
+                ..Default::default()
+            };
+
+            console::strip_ansi_codes(&module_config.render_bar(&state, &BTreeMap::new()))
+                .to_string()
+        };
+
+        assert_eq!(render(12), "left    wide");
+        assert_eq!(render(10), "left   mid");
+        assert_eq!(render(7), "left   ");
+
+        let config = BTreeMap::from([("format_left@120".to_owned(), "left".to_owned())]);
+
+        let err = ModuleConfig::new(&config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid format_left@120: expected a breakpoint like @<120"
+        );
+    }
+
+    struct StaticWidget(&'static str);
+
+    impl Widget for StaticWidget {
+        fn process(&self, _name: &str, _state: &ZellijState) -> String {
+            self.0.to_owned()
+        }
+
+        fn process_click(
+            &self,
+            _name: &str,
+            _state: &ZellijState,
+            _pos: usize,
+            _action: MouseAction,
+        ) {
+        }
+
+        fn dependencies(&self, _name: &str) -> Vec<Dependency> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_render_bar_drops_widgets() {
+        let widget_map = BTreeMap::from([
+            (
+                "a".to_owned(),
+                Arc::new(StaticWidget("aaaa")) as Arc<dyn Widget>,
+            ),
+            (
+                "b".to_owned(),
+                Arc::new(StaticWidget("bbbb")) as Arc<dyn Widget>,
+            ),
+        ]);
+
+        let render = |config: &BTreeMap<String, String>, cols: usize| {
+            let state = ZellijState {
+                cols,
+                ..Default::default()
+            };
+
+            let output = ModuleConfig::new(config)
+                .unwrap()
+                .render_bar(&state, &widget_map);
+
+            console::strip_ansi_codes(&output).to_string()
+        };
+
+        let mut config = BTreeMap::from([
+            (
+                "format_left".to_owned(),
+                "#[fg=red]{a:p1} #[fg=blue]{b:p2} end".to_owned(),
+            ),
+            ("format_right".to_owned(), "#[fg=green]right".to_owned()),
+            ("format_overflow".to_owned(), "#[fg=yellow]~".to_owned()),
+        ]);
+
+        assert_eq!(render(&config, 20), "aaaa bbbb end  right");
+        assert_eq!(render(&config, 14), "aaaa ~   right");
+        assert_eq!(render(&config, 8), "~  right");
+
+        config.insert("format_drop_order".to_owned(), "a".to_owned());
+        assert_eq!(render(&config, 16), "~bbbb end  right");
+    }
+
+    #[test]
+    fn test_render_bar_reuses_unchanged_sections() {
+        let config = BTreeMap::from([
+            (
+                "format_left".to_owned(),
+                "#[fg=red]{pipe_1} left".to_owned(),
+            ),
+            ("format_right".to_owned(), "{pipe_2}".to_owned()),
+            ("pipe_1_format".to_owned(), "{output}".to_owned()),
+            ("pipe_2_format".to_owned(), "{output}".to_owned()),
+        ]);
+        let widget_map = BTreeMap::from([(
+            "pipe".to_owned(),
+            Arc::new(PipeWidget::new(&config)) as Arc<dyn Widget>,
+        )]);
+
+        let mut module_config = ModuleConfig::new(&config).unwrap();
+        let mut state = ZellijState {
+            cols: 20,
+            pipe_results: BTreeMap::from([
+                ("pipe_1".to_owned(), "a".to_owned()),
+                ("pipe_2".to_owned(), "b".to_owned()),
+            ]),
+            ..Default::default()
+        };
+
+        let output = module_config.render_bar(&state, &widget_map);
+        assert_eq!(console::strip_ansi_codes(&output), "a left             b");
+
+        module_config.rows[0].left.last_output = Some((20, "reused".to_owned()));
+        assert!(
+            module_config
+                .render_bar(&state, &widget_map)
+                .starts_with("reused")
+        );
+
+        // a new result for pipe_2 does not render the section of pipe_1
+        state
+            .pipe_results
+            .insert("pipe_2".to_owned(), "c".to_owned());
+        state.mark_changed(Dependency::Pipe("pipe_2".to_owned()));
+
+        let output = module_config.render_bar(&state, &widget_map);
+        assert!(output.starts_with("reused"));
+        assert!(console::strip_ansi_codes(&output).ends_with('c'));
+
+        state.mark_changed(Dependency::Pipe("pipe_1".to_owned()));
+        let output = module_config.render_bar(&state, &widget_map);
+        assert_eq!(console::strip_ansi_codes(&output), "a left             c");
+    }
+
+    #[test]
+    fn test_render_bar_shows_running_commands() {
+        let config = BTreeMap::from([
+            (
+                "format_left".to_owned(),
+                "{command_pending}{?command_pending.running}{spinner}{/}".to_owned(),
+            ),
+            ("command_pending_command".to_owned(), "sleep 1".to_owned()),
+            ("command_pending_format".to_owned(), "done".to_owned()),
+            ("command_pending_interval".to_owned(), "0".to_owned()),
+            ("spinner_frames".to_owned(), "*".to_owned()),
+        ]);
+        let command_widget = Arc::new(CommandWidget::new(&config));
+        let widget_map = BTreeMap::from([
+            (
+                "command".to_owned(),
+                command_widget.clone() as Arc<dyn Widget>,
+            ),
+            (
+                "spinner".to_owned(),
+                Arc::new(SpinnerWidget::new(&config)) as Arc<dyn Widget>,
+            ),
+        ]);
+
+        let mut module_config = ModuleConfig::new(&config).unwrap();
+        let mut state = ZellijState {
+            cols: 5,
+            plugin_uuid: uuid::Uuid::new_v4().to_string(),
+            ..Default::default()
+        };
+
+        // the command is issued while rendering and shown as running with
+        // the next update
+        module_config.render_bar(&state, &widget_map);
+        assert!(command_widget.next_update(&state).is_some());
+
+        let expired = command_widget.expired(&state);
+        assert_eq!(
+            expired,
+            vec![Dependency::Command("command_pending".to_owned())]
+        );
+        for dependency in expired {
+            state.mark_changed(dependency);
+        }
+
+        let output = module_config.render_bar(&state, &widget_map);
+        assert_eq!(console::strip_ansi_codes(&output), "*    ");
+
+        state.command_results.insert(
+            "command_pending".to_owned(),
+            CommandResult {
+                context: BTreeMap::from([(
+                    "timestamp".to_owned(),
+                    Local::now().format(TIMESTAMP_FORMAT).to_string(),
+                )]),
+                ..CommandResult::default()
+            },
+        );
+        state.mark_changed(Dependency::Command("command_pending".to_owned()));
+
+        let output = module_config.render_bar(&state, &widget_map);
+        assert_eq!(console::strip_ansi_codes(&output), "done ");
+
+        let _ = std::fs::remove_file(format!("/tmp/{}.command_pending.lock", state.plugin_uuid));
+    }
+
+    #[test]
+    fn test_render_bar_with_spacers() {
+        let render = |config: &BTreeMap<String, String>, cols: usize| {
+            let state = ZellijState {
+                cols,
+                ..Default::default()
+            };
+
+            let output = ModuleConfig::new(config)
+                .unwrap()
+                .render_bar(&state, &BTreeMap::new());
+
+            console::strip_ansi_codes(&output).to_string()
+        };
+
+        let config = BTreeMap::from([(
+            "format".to_owned(),
+            "tabs{spacer}#[fg=red]git{spacer:2}clock".to_owned(),
+        )]);
+
+        assert_eq!(render(&config, 20), "tabs   git     clock");
+        assert_eq!(render(&config, 8), "tabsgitclock");
+
+        let config = BTreeMap::from([
+            ("format_left".to_owned(), "left{spacer}".to_owned()),
+            ("format_left_max_width".to_owned(), "3".to_owned()),
+            ("format_right".to_owned(), "right".to_owned()),
+            ("format_right_min_width".to_owned(), "7".to_owned()),
+        ]);
+
+        assert_eq!(render(&config, 12), "lef    right");
+
+        let config = BTreeMap::from([("format_left_min_width".to_owned(), "x".to_owned())]);
+
+        let err = ModuleConfig::new(&config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid format_left_min_width: expected a number"
+        );
+    }
+
+    #[test]
+    fn test_render_bar_truncates_links() {
+        let config = BTreeMap::from([
+            (
+                "format_left".to_owned(),
+                "#[link=https://example.com]abcdefghij".to_owned(),
+            ),
+            ("format_left_max_width".to_owned(), "4".to_owned()),
+        ]);
+
+        let mut module_config = ModuleConfig::new(&config).unwrap();
+        let state = ZellijState {
+            cols: 6,
+            ..Default::default()
+        };
+
+        let output = module_config.render_bar(&state, &BTreeMap::new());
+
+        // only the visible text is cut, the hyperlink is still closed
+        assert!(output.contains("\x1b]8;;https://example.com\x1b\\abcd\x1b]8;;\x1b\\"));
+        assert_eq!(measure_text_width(&output), 6);
+    }
+
+    struct ClickWidget {
+        output: &'static str,
+        clicks: Mutex<Vec<(String, usize, MouseAction)>>,
+    }
+
+    impl Widget for ClickWidget {
+        fn process(&self, _name: &str, _state: &ZellijState) -> String {
+            self.output.to_owned()
+        }
+
+        fn process_click(&self, name: &str, _state: &ZellijState, pos: usize, action: MouseAction) {
+            self.clicks
+                .lock()
+                .unwrap()
+                .push((name.to_owned(), pos, action));
+        }
+
+        fn dependencies(&self, _name: &str) -> Vec<Dependency> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_handle_mouse_action_uses_hits() {
+        let config = BTreeMap::from([
+            ("format_left".to_owned(), "{a} x {a}{spacer}".to_owned()),
+            ("format_right".to_owned(), "#[fg=red]{b}".to_owned()),
+            ("format_right_min_width".to_owned(), "4".to_owned()),
+        ]);
+        let widget = Arc::new(ClickWidget {
+            output: "aa",
+            clicks: Mutex::new(Vec::new()),
+        });
+        let widget_map = BTreeMap::from([
+            ("a".to_owned(), widget.clone() as Arc<dyn Widget>),
+            ("b".to_owned(), widget.clone() as Arc<dyn Widget>),
+        ]);
+        let mut state = ZellijState {
+            cols: 20,
+            ..Default::default()
+        };
+
+        let mut module_config = ModuleConfig::new(&config).unwrap();
+        let output = module_config.render_bar(&state, &widget_map);
+        assert_eq!(console::strip_ansi_codes(&output), "aa x aa           aa");
+
+        for col in [0, 6, 10, 17, 19] {
+            module_config.handle_mouse_action(&mut state, Mouse::LeftClick(0, col), &widget_map);
+        }
+
+        // scrolling goes to the widget under the cursor
+        for mouse in [
+            Mouse::ScrollUp(1),
+            Mouse::Hover(0, 1),
+            Mouse::ScrollDown(3),
+            Mouse::Release(0, 1),
+            Mouse::RightClick(0, 5),
+        ] {
+            module_config.handle_mouse_action(&mut state, mouse, &widget_map);
+        }
+
+        assert_eq!(
+            *widget.clicks.lock().unwrap(),
+            vec![
+                ("a".to_owned(), 0, MouseAction::LeftClick),
+                ("a".to_owned(), 1, MouseAction::LeftClick),
+                ("b".to_owned(), 1, MouseAction::LeftClick),
+                ("b".to_owned(), 1, MouseAction::ScrollUp),
+                ("a".to_owned(), 1, MouseAction::ScrollDown),
+                ("a".to_owned(), 0, MouseAction::RightClick),
+            ]
+        );
+
+        // the hover is kept while the cursor stays on the widget and cleared,
+        // once it leaves
+        let mut hover = |col: usize, state: &mut ZellijState| {
+            let changed =
+                module_config.handle_mouse_action(state, Mouse::Hover(0, col), &widget_map);
+            let hover = state.hover.as_ref().map(|h| (h.key.clone(), h.pos));
+
+            (changed, hover)
+        };
+
+        assert_eq!(state.hover.as_ref().map(|h| h.pos), Some(1));
+        let since = state.hover.as_ref().unwrap().since;
+        assert_eq!(hover(1, &mut state), (false, Some(("a".to_owned(), 1))));
+        assert_eq!(hover(0, &mut state), (true, Some(("a".to_owned(), 0))));
+        assert_eq!(state.hover.as_ref().unwrap().since, since);
+        assert_eq!(hover(10, &mut state), (true, None));
+        assert_eq!(hover(12, &mut state), (false, None));
+        assert!(state.changed_since(&[Dependency::Hover], 0));
+
+        // the hover expires without further hover events, as zellij does not
+        // report when the cursor leaves the pane
+        assert_eq!(hover(1, &mut state), (true, Some(("a".to_owned(), 1))));
+        assert!(!state.expire_hover());
+
+        let seen = Local::now() - Duration::seconds(HOVER_TIMEOUT_SECONDS);
+        state.hover.as_mut().unwrap().seen = seen;
+        assert_eq!(hover(1, &mut state), (false, Some(("a".to_owned(), 1))));
+        assert!(!state.expire_hover());
+
+        state.hover.as_mut().unwrap().seen = seen;
+        let revision = state.revision;
+        assert!(state.expire_hover());
+        assert_eq!(state.hover, None);
+        assert!(state.changed_since(&[Dependency::Hover], revision));
+    }
+
+    #[test]
+    fn test_click_actions_replace_widget_clicks() {
+        let mut config = BTreeMap::from([
+            ("format_left".to_owned(), "{a}{b}".to_owned()),
+            ("b_clickaction".to_owned(), "echo b".to_owned()),
+            (
+                "command_x_clickaction".to_owned(),
+                "zellij:new_tab".to_owned(),
+            ),
+        ]);
+        let widget = Arc::new(ClickWidget {
+            output: "aa",
+            clicks: Mutex::new(Vec::new()),
+        });
+        let widget_map = BTreeMap::from([
+            ("a".to_owned(), widget.clone() as Arc<dyn Widget>),
+            ("b".to_owned(), widget.clone() as Arc<dyn Widget>),
+        ]);
+        let mut state = ZellijState {
+            cols: 10,
+            ..Default::default()
+        };
+
+        let mut module_config = ModuleConfig::new(&config).unwrap();
+        assert_eq!(module_config.click_actions.len(), 1);
+        module_config.render_bar(&state, &widget_map);
+
+        for col in [1, 3] {
+            module_config.handle_mouse_action(&mut state, Mouse::LeftClick(0, col), &widget_map);
+        }
+        module_config.handle_mouse_action(&mut state, Mouse::RightClick(0, 3), &widget_map);
+
+        assert_eq!(
+            *widget.clicks.lock().unwrap(),
+            vec![
+                ("a".to_owned(), 1, MouseAction::LeftClick),
+                ("b".to_owned(), 1, MouseAction::RightClick),
+            ]
+        );
+
+        let mut middle = config.clone();
+        middle.insert("command_x_clickaction_middle".to_owned(), "echo".to_owned());
+        let err = ModuleConfig::new(&middle).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid command_x_clickaction_middle: middle clicks are not reported to plugins by zellij"
+        );
+
+        config.insert("a_scrollup".to_owned(), "zellij:switch_mode x".to_owned());
+        let err = ModuleConfig::new(&config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid a_scrollup: unknown mode in zellij:switch_mode x"
+        );
+    }
+
+    #[test]
+    fn test_handle_mouse_action_uses_click_regions() {
+        let config = BTreeMap::from([(
+            "format_left".to_owned(),
+            "#[click=zellij:new_tab] + {spacer}{a}#[fg=red]b".to_owned(),
+        )]);
+        let widget = Arc::new(ClickWidget {
+            output: "aa",
+            clicks: Mutex::new(Vec::new()),
+        });
+        let widget_map = BTreeMap::from([("a".to_owned(), widget.clone() as Arc<dyn Widget>)]);
+        let mut state = ZellijState {
+            cols: 10,
+            ..Default::default()
+        };
+
+        let mut module_config = ModuleConfig::new(&config).unwrap();
+        let output = module_config.render_bar(&state, &widget_map);
+        assert_eq!(console::strip_ansi_codes(&output), " +     aab");
+
+        // the region grows by its spacer and ends at the next style block
+        let hits = &module_config.rows[0].hits;
+        let new_tab = ClickAction::Zellij(ZellijAction::NewTab);
+        assert_eq!(hits.regions, vec![(0..9, new_tab)]);
+        assert_eq!(hits.find(8), Some(("a", 1)));
+        assert_eq!(hits.find_region(9), None);
+
+        // the widget within the region still receives its clicks
+        for mouse in [
+            Mouse::LeftClick(0, 1),
+            Mouse::LeftClick(0, 8),
+            Mouse::RightClick(0, 8),
+        ] {
+            module_config.handle_mouse_action(&mut state, mouse, &widget_map);
+        }
+
+        assert_eq!(
+            *widget.clicks.lock().unwrap(),
+            vec![
+                ("a".to_owned(), 1, MouseAction::LeftClick),
+                ("a".to_owned(), 1, MouseAction::RightClick)
+            ]
+        );
+
+        // a reloaded configuration handles clicks on the bar, as it is shown
+        let mut reloaded = ModuleConfig::new(&config).unwrap();
+        reloaded.restore_runtime_state(&module_config);
+        assert_eq!(reloaded.mouse_position, Some((0, 8)));
+
+        reloaded.handle_mouse_action(&mut state, Mouse::LeftClick(0, 7), &widget_map);
+        assert_eq!(
+            widget.clicks.lock().unwrap().last(),
+            Some(&("a".to_owned(), 0, MouseAction::LeftClick))
+        );
+    }
+
+    #[test]
+    fn test_render_bar_with_center_modes() {
+        let render = |left: &str, mode: &str, cols: usize| {
+            let config = BTreeMap::from([
+                ("format_left".to_owned(), left.to_owned()),
+                ("format_center".to_owned(), "mid".to_owned()),
+                ("format_right".to_owned(), "r".to_owned()),
+                ("format_center_mode".to_owned(), mode.to_owned()),
+                ("format_hide_on_overlength".to_owned(), "true".to_owned()),
+            ]);
+            let state = ZellijState {
+                cols,
+                ..Default::default()
+            };
+
+            let output = ModuleConfig::new(&config)
+                .unwrap()
+                .render_bar(&state, &BTreeMap::new());
+
+            console::strip_ansi_codes(&output).to_string()
+        };
+
+        assert_eq!(render("l", "screen", 20), "l        mid       r");
+        assert_eq!(render("l", "left-biased", 20), "l        mid       r");
+        assert_eq!(render("longleftxx", "screen", 20), "longleftxx         r");
+        assert_eq!(render("longleftxx", "between", 20), "longleftxx   mid   r");
+        assert_eq!(
+            render("longleftxx", "left-biased", 20),
+            "longleftxxmid      r"
+        );
+        assert_eq!(render("longleftxx", "between", 14), "longleftxxmidr");
+
+        let config = BTreeMap::from([("format_center_mode".to_owned(), "middle".to_owned())]);
+
+        let err = ModuleConfig::new(&config).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid format_center_mode: middle");
+    }
+
+    #[test]
+    fn test_module_config_reports_format_errors() {
+        let config = BTreeMap::from([(
+            "format_left".to_owned(),
+            "{mode} #[fg=red,bld]{session}".to_owned(),
+        )]);
+
+        let err = ModuleConfig::new(&config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid format_left: unknown attribute \"bld\" at column 17"
+        );
+
+        let config = BTreeMap::from([
+            ("format_left".to_owned(), "{mode}".to_owned()),
+            ("tab_active".to_owned(), "#[fg=red {name}".to_owned()),
+        ]);
+
+        let err = ModuleConfig::new(&config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid tab_active: unclosed style block at column 1"
+        );
+
+        for key in [
+            "command_x_format_hover",
+            "tab_normal_hover",
+            "tooltip_format",
+        ] {
+            let config = BTreeMap::from([(key.to_owned(), "#[bogus]{stdout}".to_owned())]);
+
+            let err = ModuleConfig::new(&config).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("Invalid {key}: unknown attribute \"bogus\" at column 3")
+            );
+        }
+
+        let config = BTreeMap::from([("style_warn".to_owned(), "fg=red,bld".to_owned())]);
+
+        let err = ModuleConfig::new(&config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid style_warn: unknown attribute \"bld\""
+        );
+
+        let config = BTreeMap::from([("color_depth".to_owned(), "88".to_owned())]);
+
+        let err = ModuleConfig::new(&config).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid color_depth: 88");
+    }
 
     #[test]
     fn test_formatted_part_from_string() {
@@ -543,6 +1828,7 @@ mod test {
                 bg: Some(RgbColor(0, 255, 0).into()),
                 effects: Effects::BOLD | Effects::ITALIC,
                 content: "foo".to_owned(),
+                nodes: vec![ContentNode::Text("foo".to_owned())],
                 ..Default::default()
             },
         )
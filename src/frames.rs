@@ -135,6 +135,18 @@ pub fn should_show_frames_for_multiple_panes(mode_info: &ModeInfo, panes: &[&Pan
     panes.len() > 1
 }
 
+/// Returns whether the plugin runs in the active tab, or `None` while the
+/// panes of the active tab are not known yet.
+pub fn is_plugin_in_active_tab(
+    tabs: &[TabInfo],
+    pane_info: &PaneManifest,
+    plugin_pane_id: PluginIds,
+) -> Option<bool> {
+    let panes = get_current_panes(tabs, pane_info)?;
+
+    Some(is_plugin_for_current_tab(&panes, plugin_pane_id))
+}
+
 fn is_plugin_for_current_tab(panes: &[PaneInfo], plugin_pane_id: PluginIds) -> bool {
     panes
         .iter()
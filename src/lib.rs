@@ -1,6 +1,9 @@
+pub mod action;
 pub mod border;
+pub mod color;
 pub mod config;
 pub mod frames;
 pub mod pipe;
 pub mod render;
+pub mod theme;
 pub mod widgets;
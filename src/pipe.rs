@@ -3,7 +3,7 @@ use std::ops::Sub;
 use chrono::{Duration, Local};
 
 use crate::{
-    config::ZellijState,
+    config::{Dependency, ZellijState},
     widgets::{command::TIMESTAMP_FORMAT, notification},
 };
 
@@ -87,6 +87,7 @@ fn pipe(state: &mut ZellijState, name: &str, content: &str) {
     state
         .pipe_results
         .insert(name.to_owned(), content.to_owned());
+    state.mark_changed(Dependency::Pipe(name.to_owned()));
 }
 
 fn notify(state: &mut ZellijState, message: &str) {
@@ -94,6 +95,7 @@ fn notify(state: &mut ZellijState, message: &str) {
         body: message.to_string(),
         received_at: Local::now(),
     });
+    state.mark_changed(Dependency::Notifications);
 }
 
 fn rerun_command(state: &mut ZellijState, command_name: &str) {
@@ -116,4 +118,5 @@ fn rerun_command(state: &mut ZellijState, command_name: &str) {
     state
         .command_results
         .insert(command_name.to_string(), command_result.clone());
+    state.mark_changed(Dependency::Command(command_name.to_owned()));
 }
@@ -1,25 +1,407 @@
-use cached::{LruCache, macros::cached};
-use lazy_static::lazy_static;
-use std::{collections::BTreeMap, sync::Arc};
+use cached::{Cached, LruCache, macros::cached};
+use std::{borrow::Cow, collections::BTreeMap, ops::Range, sync::Arc};
 
 use anstyle::{Ansi256Color, AnsiColor, Color, RgbColor, Style};
-use regex::Regex;
 use zellij_tile::prelude::bail;
 
 use crate::{
-    config::{UpdateEventMask, ZellijState, event_mask_from_widget_name},
-    widgets::widget::Widget,
+    action::ClickAction,
+    color::{self, ColorDepth},
+    config::{Dependency, ZellijState},
+    widgets::{mode::map_string_to_mode, widget::Widget},
 };
 
-lazy_static! {
-    static ref WIDGET_REGEX: Regex = Regex::new("(\\{[a-z_0-9]+\\})").unwrap();
+/// Characters that lose their special meaning when prefixed with a backslash.
+const ESCAPABLE_CHARS: [char; 7] = ['\\', '#', '[', ']', '{', '}', ','];
+
+/// Marker for a single weight of a spacer in rendered, but not yet expanded
+/// output.
+const SPACER_MARKER: char = '\u{1f}';
+
+/// Maximum depth of named styles referencing other styles.
+const MAX_STYLE_DEPTH: usize = 8;
+
+/// Error returned when a format string cannot be parsed. The column is the
+/// 1-based character position within the format string.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormatError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at column {}", self.message, self.column)
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Text(String),
+    Style(Vec<(String, usize)>),
+    Placeholder(String, usize),
+}
+
+/// A node of the content of a single formatted part.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContentNode {
+    Text(String),
+    Placeholder(Placeholder),
+    /// Flexible space like `{spacer}` or `{spacer:2}`, that shares the
+    /// remaining width of the bar with the other spacers by its weight.
+    Spacer(usize),
+}
+
+/// Columns of the rendered output, that belong to the widgets. It is recorded
+/// while rendering, so that clicks are passed to the widget under the cursor
+/// with their offset into the output of the widget.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HitMap {
+    pub hits: Vec<(Range<usize>, String)>,
+    /// Text marked with `#[click=...]` and the action to run on a click.
+    pub regions: Vec<(Range<usize>, ClickAction)>,
+    /// Columns of the unexpanded spacers, one per unit of weight.
+    pub spacers: Vec<usize>,
+}
+
+impl HitMap {
+    /// Appends the hits of output, that starts at column `offset`.
+    pub fn extend(&mut self, other: &HitMap, offset: usize) {
+        let shift = |range: &Range<usize>| range.start + offset..range.end + offset;
+
+        self.hits.extend(
+            other
+                .hits
+                .iter()
+                .map(|(range, key)| (shift(range), key.to_owned())),
+        );
+        self.regions.extend(
+            other
+                .regions
+                .iter()
+                .map(|(range, action)| (shift(range), action.clone())),
+        );
+        self.spacers
+            .extend(other.spacers.iter().map(|column| column + offset));
+    }
+
+    /// Cuts off everything after `width` columns, like [`console::truncate_str`].
+    pub fn truncate(&mut self, width: usize) {
+        let cut = |range: &mut Range<usize>| {
+            range.end = range.end.min(width);
+            range.start < range.end
+        };
+
+        self.hits.retain_mut(|(range, _)| cut(range));
+        self.regions.retain_mut(|(range, _)| cut(range));
+        self.spacers.retain(|column| *column < width);
+    }
+
+    /// Moves the hits behind the spacers, once their widths are known.
+    /// Regions grow by the spacers within them, spacers at their edges are
+    /// left out.
+    pub fn expand_spacers(&mut self, widths: &[usize]) {
+        let spacers = std::mem::take(&mut self.spacers);
+        let shift = |column: usize, inclusive: bool| -> usize {
+            spacers
+                .iter()
+                .zip(widths)
+                .filter(|(spacer, _)| **spacer < column || (inclusive && **spacer == column))
+                .map(|(_, width)| width)
+                .sum()
+        };
+        let expand = |range: &Range<usize>| {
+            range.start + shift(range.start, true)..range.end + shift(range.end, false)
+        };
+
+        for (range, _) in self.hits.iter_mut() {
+            *range = expand(range);
+        }
+
+        for (range, _) in self.regions.iter_mut() {
+            *range = expand(range);
+        }
+    }
+
+    /// Returns the action of the click region at the column.
+    pub fn find_region(&self, column: usize) -> Option<&ClickAction> {
+        self.regions
+            .iter()
+            .find(|(range, _)| range.contains(&column))
+            .map(|(_, action)| action)
+    }
+
+    /// Returns the key of the widget at the column and the offset of the
+    /// column into its output.
+    pub fn find(&self, column: usize) -> Option<(&str, usize)> {
+        self.hits
+            .iter()
+            .find(|(range, _)| range.contains(&column))
+            .map(|(range, key)| (key.as_str(), column - range.start))
+    }
+}
+
+/// A placeholder like `{name}` with an optional filter pipeline, e.g.
+/// `{name|upper|max:12|pad:8:right}`. A priority like `{name:p3}` allows the
+/// placeholder to be dropped, when the bar does not fit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placeholder {
+    pub name: String,
+    pub priority: Option<usize>,
+    pub filters: Vec<Filter>,
+    pub source: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Filter {
+    Upper,
+    Lower,
+    Trim,
+    Max(usize, String),
+    Pad(usize, console::Alignment),
+    Default(String),
+}
+
+impl Placeholder {
+    /// Parses the inner part of a placeholder. The column points to the
+    /// opening brace and is used for error reporting.
+    fn parse(source: &str, column: usize) -> Result<Self, FormatError> {
+        let mut segments = source.split('|');
+        let head = segments.next().unwrap_or_default();
+
+        let mut args = head.split(':');
+        let name = args.next().unwrap_or_default().to_owned();
+        let mut priority = None;
+        let mut arg_column = column + name.chars().count() + 2;
+
+        for arg in args {
+            match arg.strip_prefix('p').map(|p| p.parse::<usize>()) {
+                Some(Ok(p)) => priority = Some(p),
+                _ => {
+                    return Err(FormatError {
+                        column: arg_column,
+                        message: format!("invalid argument \"{arg}\""),
+                    });
+                }
+            }
+
+            arg_column += arg.chars().count() + 1;
+        }
+
+        let mut filters = Vec::new();
+        let mut filter_column = column + head.chars().count() + 2;
+
+        for segment in segments {
+            filters.push(Filter::parse(segment, filter_column)?);
+            filter_column += segment.chars().count() + 1;
+        }
+
+        Ok(Self {
+            name,
+            priority,
+            filters,
+            source: source.to_owned(),
+        })
+    }
+
+    /// Runs the value through the filter pipeline.
+    pub fn apply(&self, value: String) -> String {
+        self.filters
+            .iter()
+            .fold(value, |value, filter| filter.apply(value))
+    }
+}
+
+impl From<&str> for Placeholder {
+    fn from(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            priority: None,
+            filters: Vec::new(),
+            source: name.to_owned(),
+        }
+    }
+}
+
+impl Filter {
+    fn parse(filter: &str, column: usize) -> Result<Self, FormatError> {
+        let (name, args) = match filter.split_once(':') {
+            Some((name, args)) => (name, Some(args)),
+            None => (filter, None),
+        };
+
+        let invalid_argument = || FormatError {
+            column,
+            message: format!("invalid argument for filter \"{name}\""),
+        };
+
+        let width = |arg: Option<&str>| -> Result<usize, FormatError> {
+            arg.and_then(|w| w.parse::<usize>().ok())
+                .ok_or_else(invalid_argument)
+        };
+
+        match (name, args) {
+            ("upper", None) => Ok(Filter::Upper),
+            ("lower", None) => Ok(Filter::Lower),
+            ("trim", None) => Ok(Filter::Trim),
+            ("default", Some(text)) => Ok(Filter::Default(text.to_owned())),
+            ("max", Some(args)) => {
+                let (max, tail) = match args.split_once(':') {
+                    Some((max, tail)) => (max, tail),
+                    None => (args, ""),
+                };
+
+                Ok(Filter::Max(width(Some(max))?, tail.to_owned()))
+            }
+            ("pad", Some(args)) => {
+                let (pad, align) = match args.split_once(':') {
+                    Some((pad, align)) => (pad, align),
+                    None => (args, "left"),
+                };
+
+                let align = match align {
+                    "left" => console::Alignment::Left,
+                    "center" => console::Alignment::Center,
+                    "right" => console::Alignment::Right,
+                    _ => return Err(invalid_argument()),
+                };
+
+                Ok(Filter::Pad(width(Some(pad))?, align))
+            }
+            ("upper" | "lower" | "trim" | "default" | "max" | "pad", _) => Err(invalid_argument()),
+            _ => Err(FormatError {
+                column,
+                message: format!("unknown filter \"{name}\""),
+            }),
+        }
+    }
+
+    /// Applies the filter to the value. Widths are measured without escape
+    /// sequences, so that styled widget output is handled correctly.
+    pub fn apply(&self, value: String) -> String {
+        match self {
+            Filter::Upper => map_visible_text(&value, str::to_uppercase),
+            Filter::Lower => map_visible_text(&value, str::to_lowercase),
+            Filter::Trim => trim_visible_text(&value),
+            Filter::Max(width, tail) => truncate_text(&value, *width, tail),
+            Filter::Pad(width, align) => pad_text(&value, *width, *align),
+            Filter::Default(text) => match measure_text_width(&value) {
+                0 => text.to_owned(),
+                _ => value,
+            },
+        }
+    }
+}
+
+/// Condition of a conditional segment like `{?command_git}` or
+/// `{?mode=locked}`. Conditions of the else branch (`{:}`) are negated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    pub key: String,
+    pub value: Option<String>,
+    pub negated: bool,
+}
+
+impl Condition {
+    fn from_placeholder(condition: &str) -> Self {
+        let (key, value) = match condition.split_once('=') {
+            Some((key, value)) => (key, Some(value.to_owned())),
+            None => (condition, None),
+        };
+
+        Self {
+            key: key.to_owned(),
+            value,
+            negated: false,
+        }
+    }
+
+    /// Evaluates the condition. Without a value it holds if the widget or
+    /// placeholder renders any visible text, otherwise the rendered text
+    /// must match the value. `mode` is compared against the input mode.
+    pub fn holds<F>(&self, state: &ZellijState, mut lookup: F) -> bool
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let result = match (self.key.as_str(), &self.value) {
+            ("mode", Some(mode)) => map_string_to_mode(mode) == Some(state.mode.mode),
+            (key, value) => {
+                let output = match key {
+                    "session" => state.mode.session_name.clone(),
+                    _ => lookup(key),
+                };
+                let output = output
+                    .map(|o| console::strip_ansi_codes(&o).trim().to_owned())
+                    .unwrap_or_default();
+
+                match value {
+                    Some(value) => output == *value,
+                    None => !output.is_empty(),
+                }
+            }
+        };
+
+        result != self.negated
+    }
+}
+
+/// Color of a part, that is taken from the neighbouring parts when the bar
+/// is rendered. This allows powerline separators to follow the segments that
+/// are actually visible.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AutoColor {
+    PrevFg,
+    PrevBg,
+    NextFg,
+    NextBg,
+}
+
+impl AutoColor {
+    /// `auto` blends the part with its neighbours, i.e. the foreground takes
+    /// the background of the previous part and the background the one of the
+    /// next part.
+    fn parse(key: &str, value: &str) -> Option<Self> {
+        match (key, value) {
+            ("fg" | "bg", "prev_fg") => Some(AutoColor::PrevFg),
+            ("fg" | "bg", "prev_bg") => Some(AutoColor::PrevBg),
+            ("fg" | "bg", "next_fg") => Some(AutoColor::NextFg),
+            ("fg" | "bg", "next_bg") => Some(AutoColor::NextBg),
+            ("fg", "auto") => Some(AutoColor::PrevBg),
+            ("bg", "auto") => Some(AutoColor::NextBg),
+            _ => None,
+        }
+    }
+
+    fn resolve(&self, prev: TextColors, next: TextColors) -> Option<Color> {
+        match self {
+            AutoColor::PrevFg => prev.fg,
+            AutoColor::PrevBg => prev.bg,
+            AutoColor::NextFg => next.fg,
+            AutoColor::NextBg => next.bg,
+        }
+    }
+}
+
+/// Foreground and background color of rendered text.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextColors {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct FormattedPart {
     pub fg: Option<Color>,
     pub bg: Option<Color>,
+    pub fg_auto: Option<AutoColor>,
+    pub bg_auto: Option<AutoColor>,
     pub us: Option<Color>,
+    pub color_depth: ColorDepth,
+    pub link: Option<String>,
+    /// Action, that is run when the text of the part is clicked.
+    pub click: Option<ClickAction>,
     pub effects: anstyle::Effects,
     pub bold: bool,
     pub italic: bool,
@@ -34,9 +416,17 @@ pub struct FormattedPart {
     pub dotted_underscore: bool,
     pub dashed_underscore: bool,
     pub content: String,
-    pub cache_mask: u8,
+    pub nodes: Vec<ContentNode>,
+    pub conditions: Vec<Condition>,
+    /// Dependencies of the placeholders and conditions by key. They are
+    /// resolved on the first render, since they are declared by the widgets.
+    pub dependencies: Option<BTreeMap<String, Vec<Dependency>>>,
+    /// Revision of the state, that `cached_content` has been rendered with.
+    pub rendered_at: Option<u64>,
     pub cached_content: String,
-    pub cache: BTreeMap<String, String>,
+    pub cache: BTreeMap<String, (u64, String)>,
+    /// Widgets within `cached_content`.
+    pub hits: HitMap,
 }
 
 #[cached(
@@ -63,112 +453,491 @@ pub fn formatted_parts_from_string_cached(
     FormattedPart::multiple_from_format_string(config_string, config)
 }
 
+/// Drops all cached colors and parsed formats. Needs to be called, whenever
+/// color aliases change their value, e.g. with the zellij theme.
+pub fn clear_format_caches() {
+    PARSE_COLOR.write().cache_clear();
+    FORMATTED_PART_FROM_STRING_CACHED.write().cache_clear();
+    FORMATTED_PARTS_FROM_STRING_CACHED.write().cache_clear();
+}
+
 impl FormattedPart {
+    /// Parses a format string into its styled parts. Every `#[...]` block
+    /// starts a new part, text and `{placeholder}`s are attached to the
+    /// part they follow. Conditional segments (`{?cond}...{:}...{/}`) split
+    /// the parts and attach their conditions to every part within.
+    pub fn parse(
+        format: &str,
+        config: &BTreeMap<String, String>,
+    ) -> Result<Vec<Self>, FormatError> {
+        let mut parts: Vec<FormattedPart> = Vec::new();
+        let mut defaults: Vec<FormattedPart> = Vec::new();
+        let mut conditions: Vec<Condition> = Vec::new();
+        let mut condition_columns: Vec<usize> = Vec::new();
+        // style in force at the start of every open condition, which is
+        // restored by its `{:}` and `{/}`
+        let mut condition_styles: Vec<FormattedPart> = Vec::new();
+
+        for token in tokenize(format)? {
+            match token {
+                Token::Style(attributes) => {
+                    // blocks start from the style in force at the start of the
+                    // condition they are in, or the default style
+                    let mut part = condition_styles
+                        .last()
+                        .or(defaults.last())
+                        .map(FormattedPart::inherit_style)
+                        .unwrap_or_default();
+                    part.apply_attributes(&attributes, config, &mut defaults, 0)?;
+                    part.conditions.clone_from(&conditions);
+
+                    parts.push(part);
+                }
+                Token::Placeholder(inner, column) if inner == ":" => {
+                    match conditions.last_mut() {
+                        Some(condition) if !condition.negated => condition.negated = true,
+                        _ => {
+                            return Err(FormatError {
+                                column,
+                                message: "{:} without matching condition".to_owned(),
+                            });
+                        }
+                    }
+
+                    let style = condition_styles.last().cloned().unwrap_or_default();
+                    start_conditional_part(&mut parts, &conditions, style);
+                }
+                Token::Placeholder(inner, column) if inner == "/" => {
+                    if conditions.pop().is_none() {
+                        return Err(FormatError {
+                            column,
+                            message: "{/} without matching condition".to_owned(),
+                        });
+                    }
+                    condition_columns.pop();
+
+                    let style = condition_styles.pop().unwrap_or_default();
+                    start_conditional_part(&mut parts, &conditions, style);
+                }
+                Token::Placeholder(inner, column) if inner.starts_with('?') => {
+                    conditions.push(Condition::from_placeholder(&inner[1..]));
+                    condition_columns.push(column);
+
+                    let style = parts
+                        .last()
+                        .map(FormattedPart::continue_style)
+                        .unwrap_or_default();
+                    condition_styles.push(style.clone());
+                    start_conditional_part(&mut parts, &conditions, style);
+                }
+                Token::Text(text) => {
+                    if parts.is_empty() {
+                        parts.push(FormattedPart::default());
+                    }
+
+                    let part = parts.last_mut().unwrap();
+                    match part.nodes.last_mut() {
+                        Some(ContentNode::Text(prev)) => prev.push_str(&text),
+                        _ => part.nodes.push(ContentNode::Text(text)),
+                    }
+                }
+                Token::Placeholder(source, column) => {
+                    let node = match parse_spacer(&source, column)? {
+                        Some(weight) => ContentNode::Spacer(weight),
+                        None => ContentNode::Placeholder(Placeholder::parse(&source, column)?),
+                    };
+
+                    if parts.is_empty() {
+                        parts.push(FormattedPart::default());
+                    }
+
+                    let part = parts.last_mut().unwrap();
+                    part.nodes.push(node);
+                }
+            }
+        }
+
+        if let Some(column) = condition_columns.last() {
+            return Err(FormatError {
+                column: *column,
+                message: "unclosed condition".to_owned(),
+            });
+        }
+
+        if parts.is_empty() {
+            parts.push(FormattedPart::default());
+        }
+
+        let color_depth = ColorDepth::from_config(config);
+
+        for part in parts.iter_mut() {
+            part.color_depth = color_depth;
+            part.content = part.fill_placeholders(|_| None);
+        }
+
+        Ok(parts)
+    }
+
+    /// Lenient variant of [`FormattedPart::parse`]. Invalid format strings are
+    /// rendered as plain text, since they are already reported on load.
     pub fn multiple_from_format_string(
         config_string: &str,
         config: &BTreeMap<String, String>,
     ) -> Vec<Self> {
-        config_string
-            .split("#[")
-            .map(|s| FormattedPart::from_format_string(s, config))
-            .collect()
+        match FormattedPart::parse(config_string, config) {
+            Ok(parts) => parts,
+            Err(e) => {
+                tracing::debug!("invalid format {:?}: {}", config_string, e);
+
+                vec![FormattedPart {
+                    content: config_string.to_owned(),
+                    nodes: vec![ContentNode::Text(config_string.to_owned())],
+                    ..Default::default()
+                }]
+            }
+        }
     }
 
+    /// Parses a format string, that is expected to contain a single styled
+    /// part. Only the first part is kept.
     pub fn from_format_string(format: &str, config: &BTreeMap<String, String>) -> Self {
-        let format = match format.starts_with("#[") {
-            true => format.strip_prefix("#[").unwrap(),
-            false => format,
-        };
+        FormattedPart::multiple_from_format_string(format, config)
+            .into_iter()
+            .next()
+            .unwrap_or_default()
+    }
 
-        let mut result = FormattedPart {
-            cache_mask: cache_mask_from_content(format),
+    /// Returns a part without content, that continues the style of this part.
+    /// Automatic colors and click actions only apply to the part declaring
+    /// them.
+    fn inherit_style(&self) -> Self {
+        FormattedPart {
+            fg: self.fg,
+            bg: self.bg,
+            us: self.us,
+            color_depth: self.color_depth,
+            link: self.link.clone(),
+            effects: self.effects,
+            reverse: self.reverse,
             ..Default::default()
-        };
-
-        let mut format_content_split = format.split(']').collect::<Vec<&str>>();
-
-        if format_content_split.len() == 1 {
-            format.clone_into(&mut result.content);
+        }
+    }
 
-            return result;
+    /// Like [`FormattedPart::inherit_style`], but keeps the automatic colors
+    /// and the click action, as the style is continued by a part of a
+    /// conditional segment.
+    fn continue_style(&self) -> Self {
+        FormattedPart {
+            fg_auto: self.fg_auto,
+            bg_auto: self.bg_auto,
+            click: self.click.clone(),
+            ..self.inherit_style()
         }
+    }
 
-        let parts = format_content_split[0].split(',');
+    /// Applies the attributes of a style block on top of the inherited style.
+    /// `defaults` is the stack of styles saved with `push-default`, which are
+    /// restored by `none` and `default` colors.
+    fn apply_attributes(
+        &mut self,
+        attributes: &[(String, usize)],
+        config: &BTreeMap<String, String>,
+        defaults: &mut Vec<FormattedPart>,
+        depth: usize,
+    ) -> Result<(), FormatError> {
+        for (part, column) in attributes {
+            let part = part.as_str();
 
-        format_content_split.remove(0);
-        result.content = format_content_split.join("]");
+            if let Some(name) = part.strip_prefix("style=") {
+                self.apply_style(name, *column, config, defaults, depth)?;
 
-        for part in parts {
-            if part.starts_with("fg=") {
-                result.fg = parse_color(part.strip_prefix("fg=").unwrap(), config);
+                continue;
             }
 
-            if part.starts_with("bg=") {
-                result.bg = parse_color(part.strip_prefix("bg=").unwrap(), config);
+            match part {
+                "none" | "default" => {
+                    *self = defaults
+                        .last()
+                        .map(FormattedPart::inherit_style)
+                        .unwrap_or_default();
+
+                    continue;
+                }
+                "push-default" => {
+                    defaults.push(self.inherit_style());
+
+                    continue;
+                }
+                "pop-default" => {
+                    defaults.pop();
+
+                    continue;
+                }
+                _ => {}
             }
 
-            if part.starts_with("us=") {
-                result.us = parse_color(part.strip_prefix("us=").unwrap(), config);
+            if let Some((key, value)) = part.split_once('=') {
+                if key == "link" {
+                    self.link = Some(value.to_owned()).filter(|link| !link.is_empty());
+
+                    continue;
+                }
+
+                if key == "click" {
+                    self.click = match value.is_empty() {
+                        true => None,
+                        false => match value.parse::<ClickAction>() {
+                            Ok(action) => Some(action),
+                            Err(e) => {
+                                return Err(FormatError {
+                                    column: column + key.len() + 1,
+                                    message: format!("invalid click action: {e}"),
+                                });
+                            }
+                        },
+                    };
+
+                    continue;
+                }
+
+                if let Some(auto) = AutoColor::parse(key, value) {
+                    match key {
+                        "fg" => self.fg_auto = Some(auto),
+                        _ => self.bg_auto = Some(auto),
+                    }
+
+                    continue;
+                }
+
+                let default = defaults.last();
+                let color = match parse_color(value, config) {
+                    Some(color) => Some(color),
+                    None if value == "default" => match key {
+                        "fg" => default.and_then(|default| default.fg),
+                        "bg" => default.and_then(|default| default.bg),
+                        _ => default.and_then(|default| default.us),
+                    },
+                    None => {
+                        return Err(FormatError {
+                            column: column + key.len() + 1,
+                            message: format!("invalid color \"{value}\""),
+                        });
+                    }
+                };
+
+                match key {
+                    "fg" => (self.fg, self.fg_auto) = (color, None),
+                    "bg" => (self.bg, self.bg_auto) = (color, None),
+                    "us" => self.us = color,
+                    _ => {
+                        return Err(FormatError {
+                            column: *column,
+                            message: format!("unknown attribute \"{key}\""),
+                        });
+                    }
+                }
+
+                continue;
             }
 
             if part.eq("reverse") {
-                result.reverse = true;
+                self.reverse = true;
             }
 
-            result.parse_and_set_effect(part);
+            if !self.parse_and_set_effect(part) {
+                return Err(FormatError {
+                    column: *column,
+                    message: format!("unknown attribute \"{part}\""),
+                });
+            }
         }
 
-        result
+        Ok(())
     }
 
-    fn parse_and_set_effect(&mut self, part: &str) {
-        match part {
-            "bold" => {
-                self.effects |= anstyle::Effects::BOLD;
-            }
-            "italic" | "italics" => {
-                self.effects |= anstyle::Effects::ITALIC;
-            }
-            "underscore" => {
-                self.effects |= anstyle::Effects::UNDERLINE;
-            }
-            "blink" => {
-                self.effects |= anstyle::Effects::BLINK;
-            }
-            "hidden" => {
-                self.effects |= anstyle::Effects::HIDDEN;
-            }
-            "dim" => {
-                self.effects |= anstyle::Effects::DIMMED;
-            }
-            "strikethrough" => {
-                self.effects |= anstyle::Effects::STRIKETHROUGH;
-            }
-            "double-underscore" => {
-                self.effects |= anstyle::Effects::DOUBLE_UNDERLINE;
-            }
-            "curly-underscore" => {
-                self.effects |= anstyle::Effects::CURLY_UNDERLINE;
-            }
-            "dotted-underscore" => {
-                self.effects |= anstyle::Effects::DOTTED_UNDERLINE;
-            }
-            "dashed-underscore" => {
-                self.effects |= anstyle::Effects::DASHED_UNDERLINE;
+    /// Applies the attributes of the named style from the `style_<name>` config
+    /// key. Errors within the style are reported at the column referencing it.
+    fn apply_style(
+        &mut self,
+        name: &str,
+        column: usize,
+        config: &BTreeMap<String, String>,
+        defaults: &mut Vec<FormattedPart>,
+        depth: usize,
+    ) -> Result<(), FormatError> {
+        let Some(definition) = config.get(&format!("style_{name}")) else {
+            return Err(FormatError {
+                column,
+                message: format!("unknown style \"{name}\""),
+            });
+        };
+
+        if depth >= MAX_STYLE_DEPTH {
+            return Err(FormatError {
+                column,
+                message: format!("style \"{name}\" is nested too deep"),
+            });
+        }
+
+        let chars: Vec<char> = format!("#[{definition}]").chars().collect();
+
+        tokenize_style(&chars, 0)
+            .and_then(|(attributes, _)| {
+                self.apply_attributes(&attributes, config, defaults, depth + 1)
+            })
+            .map_err(|e| match depth {
+                0 => FormatError {
+                    column,
+                    message: format!("invalid style \"{name}\": {}", e.message),
+                },
+                _ => e,
+            })
+    }
+
+    /// Renders the content of the part by replacing all placeholders with the
+    /// result of `lookup`, passed through their filters. Placeholders without
+    /// a value are kept as they are.
+    pub fn fill_placeholders<F>(&self, mut lookup: F) -> String
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let mut output = String::new();
+
+        for node in &self.nodes {
+            match node {
+                ContentNode::Text(text) => output.push_str(text),
+                ContentNode::Spacer(weight) => push_spacer(&mut output, *weight),
+                ContentNode::Placeholder(placeholder) => match lookup(&placeholder.name) {
+                    Some(value) => output.push_str(&placeholder.apply(value)),
+                    None => {
+                        output.push('{');
+                        output.push_str(&placeholder.source);
+                        output.push('}');
+                    }
+                },
             }
-            "reverse" => {
-                self.effects |= anstyle::Effects::INVERT;
+        }
+
+        output
+    }
+
+    /// Fills the placeholders of the link target with the result of `lookup`,
+    /// e.g. to link to the output of a command.
+    pub fn fill_link<F>(&self, mut lookup: F) -> Option<String>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let link = self.link.as_ref()?;
+        let mut output = String::new();
+        let mut rest = link.as_str();
+
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+
+            let key = &rest[start + 1..start + end];
+            output.push_str(&rest[..start]);
+            match lookup(key) {
+                Some(value) => output.push_str(value.trim()),
+                None => output.push_str(&rest[start..=start + end]),
             }
-            _ => {}
+
+            rest = &rest[start + end + 1..];
+        }
+
+        output.push_str(rest);
+
+        Some(output)
+    }
+
+    /// Returns whether all conditions of the part hold, so that it should be
+    /// rendered.
+    pub fn conditions_hold<F>(&self, state: &ZellijState, mut lookup: F) -> bool
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        self.conditions
+            .iter()
+            .all(|condition| condition.holds(state, &mut lookup))
+    }
+
+    /// Like [`FormattedPart::fill_placeholders`], but returns `None` when the
+    /// part is hidden by one of its conditions.
+    pub fn render_content<F>(&self, state: &ZellijState, mut lookup: F) -> Option<String>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        if !self.conditions_hold(state, &mut lookup) {
+            return None;
+        }
+
+        Some(self.fill_placeholders(lookup))
+    }
+
+    pub fn has_auto_colors(&self) -> bool {
+        self.fg_auto.is_some() || self.bg_auto.is_some()
+    }
+
+    /// Sets the automatic colors from the colors of the neighbouring parts.
+    /// The cached output is dropped, when the colors changed since the last
+    /// render.
+    pub fn resolve_auto_colors(&mut self, prev: TextColors, next: TextColors) {
+        let fg = match self.fg_auto {
+            Some(auto) => auto.resolve(prev, next),
+            None => self.fg,
+        };
+        let bg = match self.bg_auto {
+            Some(auto) => auto.resolve(prev, next),
+            None => self.bg,
+        };
+
+        if fg != self.fg || bg != self.bg {
+            self.fg = fg;
+            self.bg = bg;
+            self.rendered_at = None;
+        }
+    }
+
+    fn parse_and_set_effect(&mut self, part: &str) -> bool {
+        if let Some(effect) = effect_by_name(part) {
+            self.effects |= effect;
+
+            return true;
+        }
+
+        // negated effects like `nobold` remove an effect of the inherited style
+        if let Some(effect) = part.strip_prefix("no").and_then(effect_by_name) {
+            self.effects = self.effects.remove(effect);
+            self.reverse &= effect != anstyle::Effects::INVERT;
+
+            return true;
         }
+
+        false
     }
 
     pub fn format_string(&self, text: &str) -> String {
+        self.format_string_with_link(text, self.link.as_deref())
+    }
+
+    /// Like [`FormattedPart::format_string`], but the text links to `link`
+    /// instead of the link of the part.
+    pub fn format_string_with_link(&self, text: &str, link: Option<&str>) -> String {
+        let text = match link {
+            Some(link) if !text.is_empty() => {
+                let link: String = link.chars().filter(|c| !c.is_control()).collect();
+
+                format!("\x1b]8;;{link}\x1b\\{text}\x1b]8;;\x1b\\")
+            }
+            _ => text.to_owned(),
+        };
+
         let mut style = Style::new();
 
-        style = style.fg_color(self.fg);
-        style = style.bg_color(self.bg);
-        style = style.underline_color(self.us);
+        style = style.fg_color(self.color_depth.convert(self.fg));
+        style = style.bg_color(self.color_depth.convert(self.bg));
+        style = style.underline_color(self.color_depth.convert(self.us));
         style = style.effects(self.effects);
 
         format!(
@@ -180,64 +949,122 @@ impl FormattedPart {
         )
     }
 
+    /// Returns whether the output of the last render is still up to date,
+    /// since none of the dependencies of its widgets changed.
+    pub fn is_fresh(
+        &mut self,
+        widgets: &BTreeMap<String, Arc<dyn Widget>>,
+        state: &ZellijState,
+    ) -> bool {
+        let dependencies = self
+            .dependencies
+            .get_or_insert_with(|| dependencies_from_nodes(&self.nodes, &self.conditions, widgets));
+
+        match self.rendered_at {
+            Some(rendered_at) => !dependencies
+                .values()
+                .any(|dependencies| state.changed_since(dependencies, rendered_at)),
+            None => false,
+        }
+    }
+
     #[tracing::instrument(skip_all)]
     pub fn format_string_with_widgets(
         &mut self,
         widgets: &BTreeMap<String, Arc<dyn Widget>>,
         state: &ZellijState,
     ) -> String {
-        let skip_cache = self.cache_mask & UpdateEventMask::Always as u8 != 0;
-
-        if !skip_cache && self.cache_mask & state.cache_mask == 0 && !self.cache.is_empty() {
+        if self.is_fresh(widgets, state) {
             tracing::debug!(msg = "hit", typ = "format_string", format = self.content);
             return self.cached_content.to_owned();
         }
         tracing::debug!(msg = "miss", typ = "format_string", format = self.content);
 
-        let mut output = self.content.clone();
+        let conditions_hold = self.conditions_hold(state, |key| {
+            widgets
+                .get(widget_name_from_key(key))
+                .map(|widget| widget.process(key, state))
+        });
 
-        for widget in WIDGET_REGEX.captures_iter(&self.content) {
-            let match_name = widget.get(0).unwrap().as_str();
-            let widget_key = match_name.trim_matches(|c| c == '{' || c == '}');
-            let mut widget_key_name = widget_key;
+        self.rendered_at = Some(state.revision);
 
-            if widget_key.starts_with("command_") {
-                widget_key_name = "command";
-            }
+        if !conditions_hold {
+            "".clone_into(&mut self.cached_content);
+            self.hits = HitMap::default();
 
-            if widget_key.starts_with("pipe_") {
-                widget_key_name = "pipe";
-            }
+            return "".to_owned();
+        }
 
-            let widget_mask = event_mask_from_widget_name(widget_key_name);
-            let skip_widget_cache = widget_mask & UpdateEventMask::Always as u8 != 0;
-            if !skip_widget_cache
-                && widget_mask & state.cache_mask == 0
-                && let Some(res) = self.cache.get(widget_key)
-            {
-                tracing::debug!(msg = "hit", typ = "widget", widget = widget_key);
-                output = output.replace(match_name, res);
-                continue;
-            }
+        let dependencies = self
+            .dependencies
+            .get_or_insert_with(|| dependencies_from_nodes(&self.nodes, &self.conditions, widgets));
+        let mut output = String::new();
+        let mut hits = HitMap::default();
+        let mut width = 0;
+
+        for node in &self.nodes {
+            let placeholder = match node {
+                ContentNode::Text(text) => {
+                    output.push_str(text);
+                    width += measure_text_width(text);
+                    continue;
+                }
+                ContentNode::Spacer(weight) => {
+                    push_spacer(&mut output, *weight);
+                    hits.spacers.extend(std::iter::repeat_n(width, *weight));
+                    continue;
+                }
+                ContentNode::Placeholder(placeholder) => placeholder,
+            };
+            let widget_key = placeholder.name.as_str();
+            let widget_dependencies = dependencies
+                .get(widget_key)
+                .map_or(&[][..], |dependencies| dependencies.as_slice());
+
+            let result = match self.cache.get(widget_key) {
+                Some((rendered_at, res))
+                    if !state.changed_since(widget_dependencies, *rendered_at) =>
+                {
+                    tracing::debug!(msg = "hit", typ = "widget", widget = widget_key);
+                    placeholder.apply(res.to_owned())
+                }
+                _ => {
+                    tracing::debug!(msg = "miss", typ = "widget", widget = widget_key);
+
+                    let result = match widgets.get(widget_name_from_key(widget_key)) {
+                        Some(widget) => widget.process(widget_key, state),
+                        None => "Use of uninitialized widget".to_owned(),
+                    };
+
+                    self.cache
+                        .insert(widget_key.to_owned(), (state.revision, result.to_owned()));
+
+                    placeholder.apply(result)
+                }
+            };
 
-            tracing::debug!(
-                msg = "miss",
-                typ = "widget",
-                widget = widget_key,
-                mask = widget_mask & state.cache_mask,
-                skip_cache = skip_cache,
-            );
-
-            let result = match widgets.get(widget_key_name) {
-                Some(widget) => widget.process(widget_key, state),
-                None => "Use of uninitialized widget".to_owned(),
+            // markers within the output of widgets, e.g. from command stdout, are
+            // not spacers of this format
+            let result = match result.contains(SPACER_MARKER) {
+                true => result.replace(SPACER_MARKER, ""),
+                false => result,
             };
 
-            self.cache.insert(widget_key.to_owned(), result.to_owned());
+            let end = width + measure_text_width(&result);
+            hits.hits.push((width..end, widget_key.to_owned()));
+            width = end;
+
+            output.push_str(&result);
+        }
 
-            output = output.replace(match_name, &result);
+        if let Some(click) = &self.click
+            && width > 0
+        {
+            hits.regions.push((0..width, click.clone()));
         }
 
+        self.hits = hits;
+
         let res = self.format_string(&output);
         self.cached_content.clone_from(&res);
 
@@ -250,7 +1077,12 @@ impl Default for FormattedPart {
         Self {
             fg: None,
             bg: None,
+            fg_auto: None,
+            bg_auto: None,
             us: None,
+            color_depth: ColorDepth::default(),
+            link: None,
+            click: None,
             effects: anstyle::Effects::new(),
             bold: false,
             italic: false,
@@ -265,70 +1097,713 @@ impl Default for FormattedPart {
             dotted_underscore: false,
             dashed_underscore: false,
             content: "".to_owned(),
-            cache_mask: 0,
+            nodes: Vec::new(),
+            conditions: Vec::new(),
+            dependencies: None,
+            rendered_at: None,
             cached_content: "".to_owned(),
             cache: BTreeMap::new(),
+            hits: HitMap::default(),
         }
     }
 }
 
-fn cache_mask_from_content(content: &str) -> u8 {
-    let mut output = 0;
-    for widget in WIDGET_REGEX.captures_iter(content) {
-        let match_name = widget.get(0).unwrap().as_str();
-        let widget_key = match_name.trim_matches(|c| c == '{' || c == '}');
-        let mut widget_key_name = widget_key;
+/// Renders the parts of a section. Parts with automatic colors are rendered
+/// after all other parts, since their colors depend on the first or last
+/// visible character of their neighbours. Hidden or empty parts are skipped,
+/// and `fallback` is used at the edges of the section.
+pub fn render_parts(
+    parts: &mut [FormattedPart],
+    widgets: &BTreeMap<String, Arc<dyn Widget>>,
+    state: &ZellijState,
+    fallback: TextColors,
+) -> String {
+    render_parts_hiding(parts, widgets, state, fallback, &[], "")
+}
 
-        if widget_key.starts_with("command_") {
-            widget_key_name = "command";
-        }
+/// Like [`render_parts`], but skips the parts marked in `hidden`. Every run of
+/// hidden parts is replaced by the `indicator`, which also counts as neighbour
+/// for automatic colors.
+pub fn render_parts_hiding(
+    parts: &mut [FormattedPart],
+    widgets: &BTreeMap<String, Arc<dyn Widget>>,
+    state: &ZellijState,
+    fallback: TextColors,
+    hidden: &[bool],
+    indicator: &str,
+) -> String {
+    let is_hidden = |index: usize| hidden.get(index).copied().unwrap_or(false);
+    let mut outputs: Vec<Option<String>> = Vec::with_capacity(parts.len());
+
+    for (index, part) in parts.iter_mut().enumerate() {
+        let output = match (is_hidden(index), part.has_auto_colors()) {
+            (true, _) if index > 0 && is_hidden(index - 1) => Some("".to_owned()),
+            (true, _) => Some(indicator.to_owned()),
+            (false, true) => None,
+            (false, false) => Some(part.format_string_with_widgets(widgets, state)),
+        };
+
+        outputs.push(output);
+    }
 
-        if widget_key.starts_with("pipe_") {
-            widget_key_name = "pipe";
+    for i in 0..parts.len() {
+        if outputs[i].is_some() {
+            continue;
         }
 
-        output |= event_mask_from_widget_name(widget_key_name);
+        let prev = outputs[..i]
+            .iter()
+            .rev()
+            .flatten()
+            .find_map(|output| edge_colors(output))
+            .map_or(fallback, |(_, last)| last);
+        let next = outputs[i + 1..]
+            .iter()
+            .flatten()
+            .find_map(|output| edge_colors(output))
+            .map_or(fallback, |(first, _)| first);
+
+        parts[i].resolve_auto_colors(prev, next);
+        outputs[i] = Some(parts[i].format_string_with_widgets(widgets, state));
     }
-    output
+
+    outputs.into_iter().flatten().collect()
 }
 
-fn hex_to_rgb(s: &str) -> anyhow::Result<Vec<u8>> {
-    if s.len() != 6 {
-        bail!("wrong hex color length");
-    }
+/// Returns the colors of the first and the last visible character of the
+/// rendered output, or `None` if nothing is visible.
+pub fn edge_colors(output: &str) -> Option<(TextColors, TextColors)> {
+    let mut current = TextColors::default();
+    let mut invert = false;
+    let mut edges = None;
+
+    for (text, is_ansi) in console::AnsiCodeIterator::new(&strip_hyperlinks(output)) {
+        if is_ansi {
+            apply_sgr(text, &mut current, &mut invert);
+            continue;
+        }
 
-    (0..s.len())
-        .step_by(2)
-        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(anyhow::Error::from))
-        .collect()
-}
+        if text.is_empty() {
+            continue;
+        }
 
-#[cached(
-    ty = "LruCache<String, Option<Color>>",
-    create = "{ LruCache::with_size(100) }",
-    convert = r#"{ (color.to_owned()) }"#
-)]
-fn parse_color(color: &str, config: &BTreeMap<String, String>) -> Option<Color> {
-    let mut color = color;
-    if color.starts_with('$') {
-        let alias_name = color.strip_prefix('$').unwrap();
+        let colors = match invert {
+            true => TextColors {
+                fg: current.bg,
+                bg: current.fg,
+            },
+            false => current,
+        };
 
-        color = config.get(&format!("color_{alias_name}"))?;
+        edges = match edges {
+            Some((first, _)) => Some((first, colors)),
+            None => Some((colors, colors)),
+        };
     }
 
-    if color.starts_with('#') {
-        let rgb = match hex_to_rgb(color.strip_prefix('#').unwrap()) {
-            Ok(rgb) => rgb,
-            Err(_) => return None,
-        };
+    edges
+}
 
-        if rgb.len() != 3 {
-            return None;
+fn apply_sgr(code: &str, colors: &mut TextColors, invert: &mut bool) {
+    let Some(params) = code
+        .strip_prefix("\x1b[")
+        .and_then(|code| code.strip_suffix('m'))
+    else {
+        return;
+    };
+
+    let mut params = params
+        .split([';', ':'])
+        .map(|param| param.parse::<u8>().unwrap_or(0));
+
+    while let Some(param) = params.next() {
+        match param {
+            0 => {
+                *colors = TextColors::default();
+                *invert = false;
+            }
+            7 => *invert = true,
+            27 => *invert = false,
+            30..=37 => colors.fg = Ansi256Color(param - 30).into_ansi().map(Color::Ansi),
+            90..=97 => colors.fg = Ansi256Color(param - 82).into_ansi().map(Color::Ansi),
+            40..=47 => colors.bg = Ansi256Color(param - 40).into_ansi().map(Color::Ansi),
+            100..=107 => colors.bg = Ansi256Color(param - 92).into_ansi().map(Color::Ansi),
+            39 => colors.fg = None,
+            49 => colors.bg = None,
+            38 | 48 | 58 => {
+                let color = match params.next() {
+                    Some(5) => params.next().map(|color| Ansi256Color(color).into()),
+                    Some(2) => match (params.next(), params.next(), params.next()) {
+                        (Some(r), Some(g), Some(b)) => Some(RgbColor(r, g, b).into()),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+
+                match param {
+                    38 => colors.fg = color,
+                    48 => colors.bg = color,
+                    _ => {}
+                }
+            }
+            _ => {}
         }
+    }
+}
 
-        return Some(
-            RgbColor(
-                *rgb.first().unwrap(),
+/// Measures the width of the text like [`console::measure_text_width`], but
+/// also skips OSC 8 hyperlinks and unexpanded spacers.
+pub fn measure_text_width(text: &str) -> usize {
+    let text = strip_hyperlinks(text);
+
+    match text.contains(SPACER_MARKER) {
+        true => console::measure_text_width(&text.replace(SPACER_MARKER, "")),
+        false => console::measure_text_width(&text),
+    }
+}
+
+/// Parses `{spacer}` and `{spacer:<weight>}`. Returns `None` for any other
+/// placeholder.
+fn parse_spacer(source: &str, column: usize) -> Result<Option<usize>, FormatError> {
+    let weight = match source.split_once(':') {
+        None if source == "spacer" => return Ok(Some(1)),
+        Some(("spacer", weight)) => weight,
+        _ => return Ok(None),
+    };
+
+    match weight.parse::<usize>() {
+        Ok(weight) if weight > 0 => Ok(Some(weight)),
+        _ => Err(FormatError {
+            column: column + "spacer".len() + 2,
+            message: format!("invalid spacer weight \"{weight}\""),
+        }),
+    }
+}
+
+/// Spacers are rendered as one marker per weight, which are replaced with the
+/// actual space by [`expand_spacers`] once the width of the whole row is
+/// known. The marker keeps the style of the surrounding part.
+fn push_spacer(output: &mut String, weight: usize) {
+    output.extend(std::iter::repeat_n(SPACER_MARKER, weight));
+}
+
+/// Returns the total weight of the spacers in the text.
+pub fn count_spacers(text: &str) -> usize {
+    text.matches(SPACER_MARKER).count()
+}
+
+/// Splits the width into `weight` shares, the remainder is distributed from
+/// the left.
+pub fn spacer_widths(weight: usize, width: usize) -> Vec<usize> {
+    (0..weight)
+        .map(|index| width / weight + (index < width % weight) as usize)
+        .collect()
+}
+
+/// Replaces the spacers of the text with `width` spaces in total.
+pub fn expand_spacers(text: &str, width: usize) -> String {
+    let mut widths = spacer_widths(count_spacers(text), width).into_iter();
+    let mut output = String::with_capacity(text.len() + width);
+
+    for c in text.chars() {
+        match c {
+            SPACER_MARKER => output.push_str(&" ".repeat(widths.next().unwrap_or_default())),
+            c => output.push(c),
+        }
+    }
+
+    output
+}
+
+/// Removes the OSC 8 sequences, that open and close hyperlinks. They are not
+/// recognized as escape sequences by `console`.
+fn strip_hyperlinks(text: &str) -> Cow<'_, str> {
+    if !text.contains("\x1b]8;") {
+        return Cow::Borrowed(text);
+    }
+
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("\x1b]8;") {
+        output.push_str(&rest[..start]);
+
+        let sequence = &rest[start..];
+        rest = &sequence[hyperlink_len(sequence)..];
+    }
+
+    output.push_str(rest);
+
+    Cow::Owned(output)
+}
+
+/// Returns the length of the OSC 8 sequence at the start of `sequence`,
+/// including its terminator.
+fn hyperlink_len(sequence: &str) -> usize {
+    match (sequence.find("\x1b\\"), sequence.find('\x07')) {
+        (Some(st), Some(bel)) if bel < st => bel + 1,
+        (Some(st), _) => st + 2,
+        (None, Some(bel)) => bel + 1,
+        (None, None) => sequence.len(),
+    }
+}
+
+/// Pads the text with spaces to `width` columns, like [`console::pad_str`].
+fn pad_text(text: &str, width: usize, align: console::Alignment) -> String {
+    let padding = width.saturating_sub(measure_text_width(text));
+
+    let (left, right) = match align {
+        console::Alignment::Left => (0, padding),
+        console::Alignment::Center => (padding / 2, padding - padding / 2),
+        console::Alignment::Right => (padding, 0),
+    };
+
+    format!("{}{text}{}", " ".repeat(left), " ".repeat(right))
+}
+
+/// Splits the text into visible text and escape sequences like
+/// [`console::AnsiCodeIterator`], but also recognizes OSC 8 hyperlinks.
+fn escape_chunks(text: &str) -> Vec<(&str, bool)> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("\x1b]8;") {
+        chunks.extend(console::AnsiCodeIterator::new(&rest[..start]));
+
+        let end = start + hyperlink_len(&rest[start..]);
+        chunks.push((&rest[start..end], true));
+
+        rest = &rest[end..];
+    }
+
+    chunks.extend(console::AnsiCodeIterator::new(rest));
+
+    chunks
+}
+
+/// Cuts off the visible text after `width` columns and appends `tail`, like
+/// [`console::truncate_str`]. All escape sequences are kept, so that styles
+/// are still reset and hyperlinks are closed after the cut.
+pub fn truncate_text(text: &str, width: usize, tail: &str) -> String {
+    if measure_text_width(text) <= width {
+        return text.to_owned();
+    }
+
+    let mut budget = width.saturating_sub(measure_text_width(tail));
+    let mut tail = Some(tail);
+    let mut output = String::with_capacity(text.len() + tail.map_or(0, str::len));
+
+    for (chunk, is_escape) in escape_chunks(text) {
+        if is_escape {
+            output.push_str(chunk);
+
+            continue;
+        }
+
+        for c in chunk.chars() {
+            let Some(cut) = tail else {
+                break;
+            };
+
+            let char_width = console::measure_text_width(c.encode_utf8(&mut [0; 4]));
+            if char_width <= budget {
+                budget -= char_width;
+                output.push(c);
+
+                continue;
+            }
+
+            output.push_str(cut);
+            tail = None;
+        }
+    }
+
+    output
+}
+
+/// Maps the key of a placeholder to the name of the widget rendering it,
+/// e.g. `command_git` is rendered by the `command` widget.
+pub fn widget_name_from_key(widget_key: &str) -> &str {
+    if widget_key.starts_with("command_") {
+        return "command";
+    }
+
+    if widget_key.starts_with("pipe_") {
+        return "pipe";
+    }
+
+    if widget_key.starts_with("spinner_") {
+        return "spinner";
+    }
+
+    widget_key
+}
+
+fn dependencies_from_nodes(
+    nodes: &[ContentNode],
+    conditions: &[Condition],
+    widgets: &BTreeMap<String, Arc<dyn Widget>>,
+) -> BTreeMap<String, Vec<Dependency>> {
+    let placeholders = nodes.iter().filter_map(|node| match node {
+        ContentNode::Placeholder(placeholder) => Some(placeholder.name.as_str()),
+        _ => None,
+    });
+    let keys = placeholders.chain(conditions.iter().map(|condition| condition.key.as_str()));
+
+    keys.map(|key| {
+        let dependencies = match key {
+            // conditions on the mode and session are evaluated without widget
+            "mode" | "session" => vec![Dependency::Mode],
+            _ => widgets
+                .get(widget_name_from_key(key))
+                .map(|widget| widget.dependencies(key))
+                .unwrap_or_default(),
+        };
+
+        (key.to_owned(), dependencies)
+    })
+    .collect()
+}
+
+fn effect_by_name(name: &str) -> Option<anstyle::Effects> {
+    match name {
+        "bold" => Some(anstyle::Effects::BOLD),
+        "italic" | "italics" => Some(anstyle::Effects::ITALIC),
+        "underscore" => Some(anstyle::Effects::UNDERLINE),
+        "blink" => Some(anstyle::Effects::BLINK),
+        "hidden" => Some(anstyle::Effects::HIDDEN),
+        "dim" => Some(anstyle::Effects::DIMMED),
+        "strikethrough" => Some(anstyle::Effects::STRIKETHROUGH),
+        "double-underscore" => Some(anstyle::Effects::DOUBLE_UNDERLINE),
+        "curly-underscore" => Some(anstyle::Effects::CURLY_UNDERLINE),
+        "dotted-underscore" => Some(anstyle::Effects::DOTTED_UNDERLINE),
+        "dashed-underscore" => Some(anstyle::Effects::DASHED_UNDERLINE),
+        "reverse" => Some(anstyle::Effects::INVERT),
+        _ => None,
+    }
+}
+
+/// Starts a new part with the given style, whenever the conditions change.
+/// A part without content yet is replaced, as its style is not in force
+/// anymore.
+fn start_conditional_part(
+    parts: &mut Vec<FormattedPart>,
+    conditions: &[Condition],
+    style: FormattedPart,
+) {
+    if parts.last().is_some_and(|part| part.nodes.is_empty()) {
+        parts.pop();
+    }
+
+    parts.push(FormattedPart {
+        conditions: conditions.to_vec(),
+        ..style
+    });
+}
+
+fn tokenize(format: &str) -> Result<Vec<Token>, FormatError> {
+    let chars: Vec<char> = format.chars().collect();
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() && ESCAPABLE_CHARS.contains(&chars[i + 1]) => {
+                text.push(chars[i + 1]);
+                i += 2;
+            }
+            '#' if chars.get(i + 1) == Some(&'[') => {
+                if !text.is_empty() {
+                    tokens.push(Token::Text(std::mem::take(&mut text)));
+                }
+
+                let (attributes, end) = tokenize_style(&chars, i)?;
+                tokens.push(Token::Style(attributes));
+                i = end + 1;
+            }
+            '{' => match placeholder_end(&chars, i) {
+                Some(end) => {
+                    if !text.is_empty() {
+                        tokens.push(Token::Text(std::mem::take(&mut text)));
+                    }
+
+                    tokens.push(Token::Placeholder(
+                        chars[i + 1..end].iter().collect(),
+                        i + 1,
+                    ));
+                    i = end + 1;
+                }
+                None => {
+                    text.push('{');
+                    i += 1;
+                }
+            },
+            c => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+
+    Ok(tokens)
+}
+
+/// Splits the attributes of the style block starting at `start` (pointing to
+/// the `#` of `#[`). Returns the trimmed attributes with their columns and
+/// the index of the closing bracket.
+fn tokenize_style(
+    chars: &[char],
+    start: usize,
+) -> Result<(Vec<(String, usize)>, usize), FormatError> {
+    let mut attributes = Vec::new();
+    let mut attribute = String::new();
+    let mut column = start + 3;
+    let mut i = start + 2;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() && ESCAPABLE_CHARS.contains(&chars[i + 1]) => {
+                attribute.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            ']' => {
+                push_attribute(&mut attributes, &mut attribute, column);
+
+                return Ok((attributes, i));
+            }
+            ',' => {
+                push_attribute(&mut attributes, &mut attribute, column);
+                column = i + 2;
+            }
+            c => attribute.push(c),
+        }
+
+        i += 1;
+    }
+
+    Err(FormatError {
+        column: start + 1,
+        message: "unclosed style block".to_owned(),
+    })
+}
+
+fn push_attribute(attributes: &mut Vec<(String, usize)>, attribute: &mut String, column: usize) {
+    let leading = attribute.len() - attribute.trim_start().len();
+    let trimmed = attribute.trim();
+
+    if !trimmed.is_empty() {
+        attributes.push((trimmed.to_owned(), column + leading));
+    }
+
+    attribute.clear();
+}
+
+/// Returns the index of the closing brace, if the brace at `start` opens a
+/// valid placeholder or conditional tag. Anything else is treated as literal
+/// text.
+fn placeholder_end(chars: &[char], start: usize) -> Option<usize> {
+    let end = start + 1 + chars[start + 1..].iter().position(|c| *c == '}')?;
+    let inner: String = chars[start + 1..end].iter().collect();
+
+    let is_valid = match inner.strip_prefix('?') {
+        Some(condition) => match condition.split_once('=') {
+            Some((key, _)) => is_condition_key(key),
+            None => is_condition_key(condition),
+        },
+        None => match inner.split_once('|') {
+            Some((name, _)) => is_placeholder_name(name),
+            None => inner == ":" || inner == "/" || is_placeholder_name(&inner),
+        },
+    };
+
+    match is_valid {
+        true => Some(end),
+        false => None,
+    }
+}
+
+/// Applies `f` to the visible text of `value`, leaving escape sequences
+/// untouched.
+fn map_visible_text<F>(value: &str, f: F) -> String
+where
+    F: Fn(&str) -> String,
+{
+    escape_chunks(value)
+        .into_iter()
+        .map(|(text, is_ansi)| match is_ansi {
+            true => text.to_owned(),
+            false => f(text),
+        })
+        .collect()
+}
+
+/// Trims whitespace at the start and end of the visible text of `value`.
+fn trim_visible_text(value: &str) -> String {
+    let mut chunks: Vec<(String, bool)> = escape_chunks(value)
+        .into_iter()
+        .map(|(text, is_ansi)| (text.to_owned(), is_ansi))
+        .collect();
+
+    for (text, _) in chunks.iter_mut().filter(|(_, is_ansi)| !is_ansi) {
+        *text = text.trim_start().to_owned();
+
+        if !text.is_empty() {
+            break;
+        }
+    }
+
+    for (text, _) in chunks.iter_mut().rev().filter(|(_, is_ansi)| !is_ansi) {
+        *text = text.trim_end().to_owned();
+
+        if !text.is_empty() {
+            break;
+        }
+    }
+
+    chunks.into_iter().map(|(text, _)| text).collect()
+}
+
+/// A placeholder name is an identifier followed by optional arguments, e.g.
+/// the priority in `{command_k8s:p3}`.
+fn is_placeholder_name(name: &str) -> bool {
+    let mut segments = name.split(':');
+
+    segments.next().is_some_and(is_identifier)
+        && segments.all(|arg| !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// Condition keys may name a property of a widget, e.g. `command_git.running`.
+fn is_condition_key(key: &str) -> bool {
+    key.split('.').all(is_identifier)
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn hex_to_rgb(s: &str) -> anyhow::Result<Vec<u8>> {
+    if s.len() != 6 {
+        bail!("wrong hex color length");
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(anyhow::Error::from))
+        .collect()
+}
+
+#[cached(
+    ty = "LruCache<String, Option<Color>>",
+    create = "{ LruCache::with_size(100) }",
+    convert = r#"{ (color.to_owned()) }"#
+)]
+fn parse_color(color: &str, config: &BTreeMap<String, String>) -> Option<Color> {
+    let expression = split_color_expression(color);
+    let (base, functions) = expression.split_first()?;
+
+    let mut result = match base
+        .strip_prefix("contrast(")
+        .and_then(|inner| inner.strip_suffix(')'))
+    {
+        Some(inner) => color::contrast(color::to_rgb(parse_color(inner, config)?)).into(),
+        None => parse_plain_color(base, config)?,
+    };
+
+    for function in functions {
+        result = apply_color_function(result, function, config)?;
+    }
+
+    Some(result)
+}
+
+/// Splits a color expression like `$blue|darken:20|mix:$bg:10` into the
+/// color and its functions. Pipes within parentheses are kept, such that
+/// `contrast($bg|lighten:10)` is a single color.
+fn split_color_expression(expression: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, c) in expression.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            '|' if depth == 0 => {
+                result.push(&expression[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    result.push(&expression[start..]);
+
+    result
+}
+
+fn apply_color_function(
+    color: Color,
+    function: &str,
+    config: &BTreeMap<String, String>,
+) -> Option<Color> {
+    let percent = |arg: Option<&str>| arg?.parse::<u8>().ok().filter(|percent| *percent <= 100);
+
+    let mut args = function.split(':');
+    let rgb = color::to_rgb(color);
+
+    let result = match args.next()? {
+        "darken" => color::darken(rgb, percent(args.next())?),
+        "lighten" => color::lighten(rgb, percent(args.next())?),
+        "mix" => {
+            let other = color::to_rgb(parse_color(args.next()?, config)?);
+            let percent = match args.next() {
+                Some(arg) => percent(Some(arg))?,
+                None => 50,
+            };
+
+            color::mix(rgb, other, percent)
+        }
+        "contrast" => color::contrast(rgb),
+        _ => return None,
+    };
+
+    if args.next().is_some() {
+        return None;
+    }
+
+    Some(result.into())
+}
+
+fn parse_plain_color(color: &str, config: &BTreeMap<String, String>) -> Option<Color> {
+    let mut color = color;
+    if color.starts_with('$') {
+        let alias_name = color.strip_prefix('$').unwrap();
+
+        color = config.get(&format!("color_{alias_name}"))?;
+    }
+
+    if color.starts_with('#') {
+        let rgb = match hex_to_rgb(color.strip_prefix('#').unwrap()) {
+            Ok(rgb) => rgb,
+            Err(_) => return None,
+        };
+
+        if rgb.len() != 3 {
+            return None;
+        }
+
+        return Some(
+            RgbColor(
+                *rgb.first().unwrap(),
                 *rgb.get(1).unwrap(),
                 *rgb.get(2).unwrap(),
             )
@@ -377,6 +1852,8 @@ fn color_by_name(color: &str) -> Option<AnsiColor> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{action::ZellijAction, widgets::widget::MouseAction};
+    use zellij_tile::prelude::{InputMode, ModeInfo};
 
     #[test]
     fn test_hex_to_rgb() {
@@ -398,6 +1875,541 @@ mod test {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_format_string() {
+        let config = BTreeMap::from([("color_accent".to_owned(), "#0000ff".to_owned())]);
+
+        let result = FormattedPart::parse("a #[fg=$accent, bold]{mode} b#[]c", &config).unwrap();
+        assert_eq!(
+            result,
+            vec![
+                FormattedPart {
+                    content: "a ".to_owned(),
+                    nodes: vec![ContentNode::Text("a ".to_owned())],
+                    ..Default::default()
+                },
+                FormattedPart {
+                    fg: Some(RgbColor(0, 0, 255).into()),
+                    effects: anstyle::Effects::BOLD,
+                    content: "{mode} b".to_owned(),
+                    nodes: vec![
+                        ContentNode::Placeholder("mode".into()),
+                        ContentNode::Text(" b".to_owned()),
+                    ],
+                    ..Default::default()
+                },
+                FormattedPart {
+                    content: "c".to_owned(),
+                    nodes: vec![ContentNode::Text("c".to_owned())],
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_style_inheritance() {
+        let config = BTreeMap::new();
+
+        let parts = FormattedPart::parse(
+            "#[fg=red,bg=blue,bold,italic]a#[fg=green]b#[fg=red,bold,push-default]c#[italic]d#[fg=green,nobold]e#[fg=default]f#[none]g#[pop-default]h#[fg=blue]i",
+            &config,
+        )
+        .unwrap();
+        let styles: Vec<(Option<Color>, Option<Color>, anstyle::Effects)> = parts
+            .iter()
+            .map(|part| (part.fg, part.bg, part.effects))
+            .collect();
+
+        let red = Some(AnsiColor::Red.into());
+        let green = Some(AnsiColor::Green.into());
+        let blue = Some(AnsiColor::Blue.into());
+        let bold = anstyle::Effects::BOLD;
+        let bold_italic = anstyle::Effects::BOLD | anstyle::Effects::ITALIC;
+
+        // every block starts from the style saved with `push-default`
+        assert_eq!(
+            styles,
+            vec![
+                (red, blue, bold_italic),
+                (green, None, anstyle::Effects::new()),
+                (red, None, bold),
+                (red, None, bold_italic),
+                (green, None, anstyle::Effects::new()),
+                (red, None, bold),
+                (red, None, bold),
+                (red, None, bold),
+                (blue, None, anstyle::Effects::new()),
+            ]
+        );
+
+        // within a condition, blocks start from the style at its start
+        let parts = FormattedPart::parse("#[fg=red,bold]{?full}#[italic]a{/}b", &config).unwrap();
+        let styles: Vec<(Option<Color>, anstyle::Effects)> =
+            parts.iter().map(|part| (part.fg, part.effects)).collect();
+
+        assert_eq!(styles, vec![(red, bold), (red, bold_italic), (red, bold)]);
+    }
+
+    #[test]
+    fn test_style_inheritance_of_separators() {
+        let config = BTreeMap::new();
+
+        let parts = FormattedPart::parse(
+            "#[fg=blue,bold]{session}#[fg=red]|#[fg=red,nobold]|#[none,fg=red]|",
+            &config,
+        )
+        .unwrap();
+        let styles: Vec<(Option<Color>, anstyle::Effects)> =
+            parts.iter().map(|part| (part.fg, part.effects)).collect();
+
+        let red = Some(AnsiColor::Red.into());
+
+        // an unstyled separator does not continue the bold block before it
+        assert_eq!(
+            styles,
+            vec![
+                (Some(AnsiColor::Blue.into()), anstyle::Effects::BOLD),
+                (red, anstyle::Effects::new()),
+                (red, anstyle::Effects::new()),
+                (red, anstyle::Effects::new()),
+            ]
+        );
+        assert_eq!(parts[1].format_string("|"), "\u{1b}[0m\u{1b}[31m|\u{1b}[0m");
+    }
+
+    #[test]
+    fn test_parse_format_string_with_escapes() {
+        let result =
+            FormattedPart::parse("\\#[x\\] \\{mode\\} [] {} {Foo}", &BTreeMap::new()).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].nodes,
+            vec![ContentNode::Text("#[x] {mode} [] {} {Foo}".to_owned())]
+        );
+        assert_eq!(
+            result[0].fill_placeholders(|_| Some("x".to_owned())),
+            "#[x] {mode} [] {} {Foo}"
+        );
+    }
+
+    #[test]
+    fn test_parse_format_string_errors() {
+        let config = BTreeMap::new();
+
+        let err = FormattedPart::parse("ab #[fg=red,bld]", &config).unwrap_err();
+        assert_eq!(err.to_string(), "unknown attribute \"bld\" at column 13");
+
+        let err = FormattedPart::parse("#[fg=#12345]", &config).unwrap_err();
+        assert_eq!(err.to_string(), "invalid color \"#12345\" at column 6");
+
+        let err = FormattedPart::parse("ab #[fg=red", &config).unwrap_err();
+        assert_eq!(err.to_string(), "unclosed style block at column 4");
+
+        let err = FormattedPart::parse("#[ux=red]", &config).unwrap_err();
+        assert_eq!(err.to_string(), "unknown attribute \"ux\" at column 3");
+    }
+
+    struct StaticWidget(&'static str);
+
+    impl Widget for StaticWidget {
+        fn process(&self, _name: &str, _state: &ZellijState) -> String {
+            self.0.to_owned()
+        }
+
+        fn process_click(
+            &self,
+            _name: &str,
+            _state: &ZellijState,
+            _pos: usize,
+            _action: MouseAction,
+        ) {
+        }
+
+        fn dependencies(&self, _name: &str) -> Vec<Dependency> {
+            Vec::new()
+        }
+    }
+
+    fn test_widgets() -> BTreeMap<String, Arc<dyn Widget>> {
+        BTreeMap::from([
+            (
+                "full".to_owned(),
+                Arc::new(StaticWidget("x")) as Arc<dyn Widget>,
+            ),
+            (
+                "empty".to_owned(),
+                Arc::new(StaticWidget("")) as Arc<dyn Widget>,
+            ),
+            (
+                "marker".to_owned(),
+                Arc::new(StaticWidget("a\u{1f}b")) as Arc<dyn Widget>,
+            ),
+        ])
+    }
+
+    fn render_format(format: &str, state: &ZellijState) -> String {
+        let widgets = test_widgets();
+
+        FormattedPart::parse(format, &BTreeMap::new())
+            .unwrap()
+            .iter_mut()
+            .map(|part| {
+                console::strip_ansi_codes(&part.format_string_with_widgets(&widgets, state))
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_widget_output_is_no_spacer() {
+        let mut parts = FormattedPart::parse("{marker}{spacer}", &BTreeMap::new()).unwrap();
+
+        let output = parts[0].format_string_with_widgets(&test_widgets(), &ZellijState::default());
+
+        assert_eq!(count_spacers(&output), 1);
+        assert_eq!(parts[0].hits.spacers, vec![2]);
+        assert_eq!(
+            expand_spacers(&console::strip_ansi_codes(&output), 3),
+            "ab   "
+        );
+    }
+
+    #[test]
+    fn test_conditional_segments() {
+        let state = ZellijState::default();
+
+        assert_eq!(
+            render_format("a{?full}#[fg=red]b{full}{/}c", &state),
+            "abxc"
+        );
+        assert_eq!(
+            render_format("a{?empty}#[fg=red]b{empty}{/}c", &state),
+            "ac"
+        );
+        assert_eq!(render_format("{?empty}a{:}b{/}", &state), "b");
+        assert_eq!(render_format("{?full}{?empty}a{:}b{/}{/}", &state), "b");
+        assert_eq!(render_format("{?full=x}a{/}{?full=y}b{/}", &state), "a");
+        assert_eq!(render_format("{?unknown}a{:}b{/}", &state), "b");
+
+        let state = ZellijState {
+            mode: ModeInfo {
+                mode: InputMode::Locked,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(render_format("{?mode=locked}L{:}U{/}", &state), "L");
+        assert_eq!(render_format("{?mode=normal}N{/}", &state), "");
+    }
+
+    #[test]
+    fn test_conditional_segments_keep_style() {
+        let result = FormattedPart::parse("#[fg=red]a{?full}b{/}c", &BTreeMap::new()).unwrap();
+
+        assert_eq!(result.len(), 3);
+        assert!(
+            result
+                .iter()
+                .all(|part| part.fg == Some(AnsiColor::Red.into()))
+        );
+        assert_eq!(result[0].conditions, vec![]);
+        assert_eq!(
+            result[1].conditions,
+            vec![Condition {
+                key: "full".to_owned(),
+                value: None,
+                negated: false,
+            }]
+        );
+        assert_eq!(result[2].conditions, vec![]);
+    }
+
+    #[test]
+    fn test_conditional_segments_restore_style() {
+        let result =
+            FormattedPart::parse("{?full}#[fg=red,bold]A{:}B{/}C", &BTreeMap::new()).unwrap();
+        let styles: Vec<(Option<Color>, anstyle::Effects)> = result
+            .iter()
+            .filter(|part| !part.nodes.is_empty())
+            .map(|part| (part.fg, part.effects))
+            .collect();
+
+        // the else branch and the text after it do not continue the style of
+        // the hidden branch
+        assert_eq!(
+            styles,
+            vec![
+                (Some(AnsiColor::Red.into()), anstyle::Effects::BOLD),
+                (None, anstyle::Effects::new()),
+                (None, anstyle::Effects::new()),
+            ]
+        );
+        assert_eq!(result.last().unwrap().conditions, vec![]);
+
+        let result =
+            FormattedPart::parse("#[fg=blue]{?full}#[fg=red]A{/}C", &BTreeMap::new()).unwrap();
+        assert_eq!(result.last().unwrap().fg, Some(AnsiColor::Blue.into()));
+    }
+
+    #[test]
+    fn test_conditional_segments_errors() {
+        let config = BTreeMap::new();
+
+        let err = FormattedPart::parse("a{?full}b", &config).unwrap_err();
+        assert_eq!(err.to_string(), "unclosed condition at column 2");
+
+        let err = FormattedPart::parse("a{/}", &config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "{/} without matching condition at column 2"
+        );
+
+        let err = FormattedPart::parse("{?full}a{:}b{:}c{/}", &config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "{:} without matching condition at column 13"
+        );
+    }
+
+    #[test]
+    fn test_placeholder_filters() {
+        let part = FormattedPart::from_format_string(
+            "[{name|upper}] [{name|max:4:…}] [{name|pad:8:right}] [{empty|default:none}] [{name|lower|max:3|pad:5:center}] [{padded|trim}]",
+            &BTreeMap::new(),
+        );
+
+        let result = part.fill_placeholders(|key| match key {
+            "name" => Some("Abcdef".to_owned()),
+            "empty" => Some("".to_owned()),
+            "padded" => Some("  x ".to_owned()),
+            _ => None,
+        });
+        assert_eq!(result, "[ABCDEF] [Abc…] [  Abcdef] [none] [ abc ] [x]");
+    }
+
+    #[test]
+    fn test_placeholder_filters_keep_escape_sequences() {
+        let styled = FormattedPart {
+            fg: Some(AnsiColor::Red.into()),
+            ..Default::default()
+        }
+        .format_string(" foo ");
+
+        let result = Filter::Upper.apply(styled.clone());
+        assert_eq!(console::strip_ansi_codes(&result), " FOO ");
+        assert_eq!(
+            result.to_lowercase().replace("foo", "FOO"),
+            styled.to_lowercase().replace("foo", "FOO")
+        );
+
+        let result = Filter::Trim.apply(styled);
+        assert_eq!(console::strip_ansi_codes(&result), "foo");
+
+        let linked = FormattedPart {
+            link: Some("https://example.com".to_owned()),
+            ..Default::default()
+        }
+        .format_string("abcdef");
+
+        let result = Filter::Max(4, "…".to_owned()).apply(linked.clone());
+        assert!(result.contains("\x1b]8;;https://example.com\x1b\\abc…\x1b]8;;\x1b\\"));
+        assert_eq!(measure_text_width(&result), 4);
+
+        let result = Filter::Pad(8, console::Alignment::Right).apply(linked.clone());
+        assert!(result.starts_with("  \x1b"));
+        assert_eq!(measure_text_width(&result), 8);
+
+        let result = Filter::Upper.apply(linked);
+        assert!(result.contains("https://example.com"));
+    }
+
+    #[test]
+    fn test_placeholder_filter_errors() {
+        let config = BTreeMap::new();
+
+        let err = FormattedPart::parse("ab {name|upper|bogus}", &config).unwrap_err();
+        assert_eq!(err.to_string(), "unknown filter \"bogus\" at column 16");
+
+        let err = FormattedPart::parse("{name|max:x}", &config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid argument for filter \"max\" at column 7"
+        );
+
+        let err = FormattedPart::parse("{name|pad:3:top}", &config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid argument for filter \"pad\" at column 7"
+        );
+
+        let err = FormattedPart::parse("ab {spacer:0}", &config).unwrap_err();
+        assert_eq!(err.to_string(), "invalid spacer weight \"0\" at column 12");
+
+        let err = FormattedPart::parse("ab {name:p1:top}", &config).unwrap_err();
+        assert_eq!(err.to_string(), "invalid argument \"top\" at column 13");
+    }
+
+    #[test]
+    fn test_auto_colors() {
+        let config = BTreeMap::new();
+        let widgets = test_widgets();
+        let state = ZellijState::default();
+        let fallback = TextColors {
+            fg: None,
+            bg: Some(AnsiColor::Black.into()),
+        };
+
+        let mut parts = FormattedPart::parse(
+            "#[bg=blue]a#[fg=auto,bg=auto]>#[bg=red]{empty}#[bg=green]b#[fg=prev_bg,bg=default]>",
+            &config,
+        )
+        .unwrap();
+        assert_eq!(parts[1].fg_auto, Some(AutoColor::PrevBg));
+        assert_eq!(parts[1].bg_auto, Some(AutoColor::NextBg));
+
+        let output = super::render_parts(&mut parts, &widgets, &state, fallback);
+        assert_eq!(console::strip_ansi_codes(&output), "a>b>");
+
+        assert_eq!(parts[1].fg, Some(AnsiColor::Blue.into()));
+        assert_eq!(parts[1].bg, Some(AnsiColor::Green.into()));
+        assert_eq!(parts[4].fg, Some(AnsiColor::Green.into()));
+        assert_eq!(parts[4].bg, None);
+
+        let edges = edge_colors(&parts[4].format_string(">")).unwrap();
+        assert_eq!(edges.0.fg, Some(AnsiColor::Green.into()));
+
+        let err = FormattedPart::parse("#[us=auto]", &config).unwrap_err();
+        assert_eq!(err.to_string(), "invalid color \"auto\" at column 6");
+    }
+
+    #[test]
+    fn test_edge_colors() {
+        let part = FormattedPart {
+            fg: Some(RgbColor(1, 2, 3).into()),
+            bg: Some(Ansi256Color(42).into()),
+            ..Default::default()
+        };
+
+        let output = format!("{}{}", part.format_string("a"), "\x1b[7;101mb\x1b[0m");
+        let (first, last) = edge_colors(&output).unwrap();
+        assert_eq!(first.fg, part.fg);
+        assert_eq!(first.bg, part.bg);
+        assert_eq!(last.fg, Some(AnsiColor::BrightRed.into()));
+        assert_eq!(last.bg, None);
+
+        assert_eq!(edge_colors(&part.format_string("")), None);
+    }
+
+    #[test]
+    fn test_format_string_with_color_depth() {
+        let mut config = BTreeMap::from([("color_depth".to_owned(), "none".to_owned())]);
+
+        let part = FormattedPart::from_format_string("#[fg=#ff0000,bg=blue,bold]", &config);
+        assert_eq!(part.color_depth, ColorDepth::None);
+        assert_eq!(part.format_string("a"), "\x1b[0m\x1b[1ma\x1b[0m");
+
+        config.insert("color_depth".to_owned(), "16".to_owned());
+
+        let part = FormattedPart::from_format_string("#[fg=#ff0000]", &config);
+        assert_eq!(part.format_string("a"), "\x1b[0m\x1b[91ma\x1b[0m");
+    }
+
+    #[test]
+    fn test_hyperlinks() {
+        let config = BTreeMap::new();
+
+        let part = FormattedPart::from_format_string(
+            "#[fg=red,link=https://example.com/?a=1\\,2]",
+            &config,
+        );
+        assert_eq!(part.link, Some("https://example.com/?a=1,2".to_owned()));
+
+        let output = part.format_string("PR");
+        assert!(output.contains("\x1b]8;;https://example.com/?a=1,2\x1b\\PR\x1b]8;;\x1b\\"));
+        assert_eq!(measure_text_width(&output), 2);
+        assert_eq!(measure_text_width("a\x1b]8;;x\x07b\x1b]8;;\x07"), 2);
+        assert_eq!(
+            edge_colors(&output).map(|(first, _)| first.fg),
+            Some(Some(AnsiColor::Red.into()))
+        );
+
+        let part = FormattedPart::from_format_string("#[link=https://x/{stdout}/{y}]", &config);
+        let link = part.fill_link(|key| match key {
+            "stdout" => Some("42\n".to_owned()),
+            _ => None,
+        });
+        assert_eq!(link, Some("https://x/42/{y}".to_owned()));
+    }
+
+    #[test]
+    fn test_click_attribute() {
+        let config = BTreeMap::new();
+
+        let parts = FormattedPart::parse(
+            "#[click=zellij:switch_mode locked] + #[fg=red]x#[click=]y",
+            &config,
+        )
+        .unwrap();
+        let locked = Some(ClickAction::Zellij(ZellijAction::SwitchMode(
+            InputMode::Locked,
+        )));
+        assert_eq!(parts[0].click, locked);
+        assert_eq!(parts[1].click, None);
+        assert_eq!(parts[2].click, None);
+
+        // conditional segments continue the region until the next block
+        let parts = FormattedPart::parse(
+            "#[click=zellij:switch_mode locked]+{?full}x{/}y#[fg=red]z",
+            &config,
+        )
+        .unwrap();
+        let clicks: Vec<bool> = parts.iter().map(|part| part.click.is_some()).collect();
+        assert_eq!(clicks, vec![true, true, true, false]);
+
+        let err = FormattedPart::parse("#[bold,click=zellij:nope]", &config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid click action: unknown zellij action zellij:nope at column 14"
+        );
+    }
+
+    #[test]
+    fn test_named_styles() {
+        let config = BTreeMap::from([
+            ("style_warning".to_owned(), "fg=red, bold".to_owned()),
+            ("style_alert".to_owned(), "style=warning,bg=blue".to_owned()),
+            ("style_broken".to_owned(), "fg=nocolor".to_owned()),
+            ("style_loop".to_owned(), "style=loop".to_owned()),
+        ]);
+
+        let part = FormattedPart::from_format_string("#[style=alert,italic]", &config);
+        assert_eq!(part.fg, Some(AnsiColor::Red.into()));
+        assert_eq!(part.bg, Some(AnsiColor::Blue.into()));
+        assert_eq!(
+            part.effects,
+            anstyle::Effects::BOLD | anstyle::Effects::ITALIC
+        );
+
+        let part = FormattedPart::from_format_string("#[style=warning,fg=green]", &config);
+        assert_eq!(part.fg, Some(AnsiColor::Green.into()));
+
+        let err = FormattedPart::parse("ab#[bold,style=missing]", &config).unwrap_err();
+        assert_eq!(err.to_string(), "unknown style \"missing\" at column 10");
+
+        let err = FormattedPart::parse("#[style=broken]", &config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid style \"broken\": invalid color \"nocolor\" at column 3"
+        );
+
+        let err = FormattedPart::parse("#[style=loop]", &config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid style \"loop\": style \"loop\" is nested too deep at column 3"
+        );
+    }
+
     #[test]
     fn test_parse_color() {
         let mut config: BTreeMap<String, String> = BTreeMap::new();
@@ -424,4 +2436,35 @@ mod test {
         let result = parse_color("$blue", &config);
         assert_eq!(result, None);
     }
+
+    #[test]
+    fn test_parse_color_functions() {
+        let config = BTreeMap::from([
+            ("color_fnbase".to_owned(), "#6464c8".to_owned()),
+            ("color_fnmix".to_owned(), "#000000".to_owned()),
+        ]);
+
+        let result = parse_color("$fnbase|darken:50", &config);
+        assert_eq!(result, Some(RgbColor(50, 50, 100).into()));
+
+        let result = parse_color("#6464c8|lighten:100|mix:$fnmix:25", &config);
+        assert_eq!(result, Some(RgbColor(191, 191, 191).into()));
+
+        let result = parse_color("#6464c8|mix:$fnmix", &config);
+        assert_eq!(result, Some(RgbColor(50, 50, 100).into()));
+
+        let result = parse_color("contrast($fnbase|lighten:80)", &config);
+        assert_eq!(result, Some(RgbColor(0, 0, 0).into()));
+
+        let result = parse_color("#101010|contrast", &config);
+        assert_eq!(result, Some(RgbColor(255, 255, 255).into()));
+
+        assert_eq!(parse_color("#6464c8|darken:101", &config), None);
+        assert_eq!(parse_color("#6464c8|darken", &config), None);
+        assert_eq!(parse_color("#6464c8|blur:2", &config), None);
+        assert_eq!(parse_color("#6464c8|darken:2:3", &config), None);
+
+        let err = FormattedPart::parse("#[fg=red|shade:2]", &config).unwrap_err();
+        assert_eq!(err.to_string(), "invalid color \"red|shade:2\" at column 6");
+    }
 }
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use zellij_tile::prelude::{PaletteColor, StyleDeclaration, Styling};
+
+/// Returns whether any format references a color of the zellij theme, e.g.
+/// `$zellij.ribbon_selected.base`.
+pub fn uses_theme_colors(config: &BTreeMap<String, String>) -> bool {
+    config.values().any(|value| value.contains("$zellij."))
+}
+
+/// Adds the palette of the zellij theme as `color_zellij.<component>.<color>`
+/// aliases to the configuration, so that they can be used like any other
+/// color alias.
+pub fn with_theme_colors(
+    config: &BTreeMap<String, String>,
+    styling: &Styling,
+) -> BTreeMap<String, String> {
+    let mut config = config.clone();
+
+    for (name, color) in theme_colors(styling) {
+        config.insert(format!("color_zellij.{name}"), color);
+    }
+
+    config
+}
+
+fn theme_colors(styling: &Styling) -> Vec<(String, String)> {
+    let declarations: [(&str, StyleDeclaration); 14] = [
+        ("text_unselected", styling.text_unselected),
+        ("text_selected", styling.text_selected),
+        ("ribbon_unselected", styling.ribbon_unselected),
+        ("ribbon_selected", styling.ribbon_selected),
+        ("table_title", styling.table_title),
+        ("table_cell_unselected", styling.table_cell_unselected),
+        ("table_cell_selected", styling.table_cell_selected),
+        ("list_unselected", styling.list_unselected),
+        ("list_selected", styling.list_selected),
+        // themes may omit the unselected frame, it is drawn like regular text then
+        (
+            "frame_unselected",
+            styling.frame_unselected.unwrap_or(styling.text_unselected),
+        ),
+        ("frame_selected", styling.frame_selected),
+        ("frame_highlight", styling.frame_highlight),
+        ("exit_code_success", styling.exit_code_success),
+        ("exit_code_error", styling.exit_code_error),
+    ];
+
+    let mut colors = Vec::new();
+
+    for (name, declaration) in declarations {
+        let fields = [
+            ("base", declaration.base),
+            ("background", declaration.background),
+            ("emphasis_0", declaration.emphasis_0),
+            ("emphasis_1", declaration.emphasis_1),
+            ("emphasis_2", declaration.emphasis_2),
+            ("emphasis_3", declaration.emphasis_3),
+        ];
+
+        for (field, color) in fields {
+            colors.push((format!("{name}.{field}"), palette_color_to_string(color)));
+        }
+    }
+
+    let players = styling.multiplayer_user_colors;
+    let players = [
+        players.player_1,
+        players.player_2,
+        players.player_3,
+        players.player_4,
+        players.player_5,
+        players.player_6,
+        players.player_7,
+        players.player_8,
+        players.player_9,
+        players.player_10,
+    ];
+
+    for (index, color) in players.into_iter().enumerate() {
+        colors.push((
+            format!("multiplayer_user_colors.player_{}", index + 1),
+            palette_color_to_string(color),
+        ));
+    }
+
+    colors
+}
+
+fn palette_color_to_string(color: PaletteColor) -> String {
+    match color {
+        PaletteColor::Rgb((r, g, b)) => format!("#{r:02x}{g:02x}{b:02x}"),
+        PaletteColor::EightBit(color) => color.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_with_theme_colors() {
+        let mut styling = Styling::default();
+        styling.ribbon_selected.base = PaletteColor::Rgb((1, 2, 255));
+        styling.text_unselected.emphasis_0 = PaletteColor::EightBit(42);
+        styling.frame_unselected = None;
+
+        let config = BTreeMap::from([(
+            "format_left".to_owned(),
+            "#[fg=$zellij.ribbon_selected.base]".to_owned(),
+        )]);
+        assert!(uses_theme_colors(&config));
+
+        let config = with_theme_colors(&config, &styling);
+        assert_eq!(
+            config.get("color_zellij.ribbon_selected.base"),
+            Some(&"#0102ff".to_owned())
+        );
+        assert_eq!(
+            config.get("color_zellij.text_unselected.emphasis_0"),
+            Some(&"42".to_owned())
+        );
+        assert_eq!(
+            config.get("color_zellij.frame_unselected.emphasis_0"),
+            Some(&"42".to_owned())
+        );
+        assert!(config.contains_key("color_zellij.multiplayer_user_colors.player_10"));
+    }
+}
@@ -1,10 +1,12 @@
 use kdl::{KdlDocument, KdlError};
 use lazy_static::lazy_static;
 use std::{
-    collections::BTreeMap,
+    any::Any,
+    collections::{BTreeMap, BTreeSet},
     fs::{File, remove_file},
     ops::Sub,
     path::{Path, PathBuf},
+    sync::Mutex,
 };
 
 use chrono::{DateTime, Duration, Local};
@@ -14,7 +16,11 @@ use zellij_tile::shim::{run_command, run_command_with_env_variables_and_cwd};
 
 use crate::render::{FormattedPart, formatted_parts_from_string_cached};
 
-use crate::{config::ZellijState, widgets::widget::Widget};
+use crate::{
+    action::{ClickAction, parse_mouse_action_key},
+    config::{Dependency, ZellijState},
+    widgets::widget::{MouseAction, Widget},
+};
 
 pub const TIMESTAMP_FORMAT: &str = "%s";
 
@@ -22,6 +28,13 @@ lazy_static! {
     static ref COMMAND_REGEX: Regex = Regex::new("_[a-zA-Z0-9]+$").unwrap();
 }
 
+/// Suffix of the format, that is used while the cursor is over the widget.
+const HOVER_FORMAT_KEY: &str = "_format_hover";
+
+/// Suffix of the condition, that holds while a command runs, e.g.
+/// `{?command_git.running}`.
+const RUNNING_SUFFIX: &str = ".running";
+
 #[derive(Clone, Debug, PartialEq)]
 enum RenderMode {
     Static,
@@ -33,11 +46,12 @@ enum RenderMode {
 struct CommandConfig {
     command: String,
     format: Vec<FormattedPart>,
+    hover_format: Option<Vec<FormattedPart>>,
     env: Option<BTreeMap<String, String>>,
     cwd: Option<PathBuf>,
     interval: i64,
     render_mode: RenderMode,
-    click_action: String,
+    click_actions: BTreeMap<MouseAction, ClickAction>,
     hide_on_empty_stdout: bool,
 }
 
@@ -52,6 +66,10 @@ pub struct CommandResult {
 pub struct CommandWidget {
     config: BTreeMap<String, CommandConfig>,
     zj_conf: BTreeMap<String, String>,
+    // timestamps of the runs, that were issued, by command name
+    issued: Mutex<BTreeMap<String, i64>>,
+    // commands, whose running state is not rendered yet
+    started: Mutex<BTreeSet<String>>,
 }
 
 impl CommandWidget {
@@ -59,12 +77,37 @@ impl CommandWidget {
         Self {
             config: parse_config(config),
             zj_conf: config.clone(),
+            issued: Mutex::new(BTreeMap::new()),
+            started: Mutex::new(BTreeSet::new()),
         }
     }
+
+    /// A command runs from the moment it is issued until a result with the
+    /// same timestamp arrives.
+    fn is_running(&self, name: &str, state: &ZellijState) -> bool {
+        let Some(issued) = self.issued.lock().unwrap().get(name).copied() else {
+            return false;
+        };
+
+        let finished = state
+            .command_results
+            .get(name)
+            .and_then(|result| result.context.get("timestamp"))
+            .and_then(|timestamp| timestamp.parse::<i64>().ok());
+
+        finished.is_none_or(|finished| finished < issued)
+    }
 }
 
 impl Widget for CommandWidget {
     fn process(&self, name: &str, state: &ZellijState) -> String {
+        if let Some(name) = name.strip_suffix(RUNNING_SUFFIX) {
+            return match self.is_running(name, state) {
+                true => "running".to_owned(),
+                false => "".to_owned(),
+            };
+        }
+
         let command_config = match self.config.get(name) {
             Some(cc) => cc,
             None => {
@@ -72,7 +115,13 @@ impl Widget for CommandWidget {
             }
         };
 
-        run_command_if_needed(command_config.clone(), name, state);
+        if let Some(ts) = run_command_if_needed(command_config, name, state) {
+            self.issued
+                .lock()
+                .unwrap()
+                .insert(name.to_owned(), ts.timestamp());
+            self.started.lock().unwrap().insert(name.to_owned());
+        }
 
         let command_result = match state.command_results.get(name) {
             Some(cr) => cr,
@@ -85,44 +134,45 @@ impl Widget for CommandWidget {
             return "".to_owned();
         }
 
-        let content = command_config
-            .format
-            .iter()
-            .map(|f| {
-                let mut content = f.content.clone();
-
-                if content.contains("{exit_code}") {
-                    content = content.replace(
-                        "{exit_code}",
-                        format!("{}", command_result.exit_code.unwrap_or(-1)).as_str(),
-                    );
-                }
+        let lookup = |key: &str| match key {
+            "exit_code" => Some(format!("{}", command_result.exit_code.unwrap_or(-1))),
+            "stdout" => Some(
+                command_result
+                    .stdout
+                    .strip_suffix('\n')
+                    .unwrap_or(&command_result.stdout)
+                    .to_owned(),
+            ),
+            "stderr" => Some(
+                command_result
+                    .stderr
+                    .strip_suffix('\n')
+                    .unwrap_or(&command_result.stderr)
+                    .to_owned(),
+            ),
+            _ => None,
+        };
 
-                if content.contains("{stdout}") {
-                    content = content.replace(
-                        "{stdout}",
-                        command_result
-                            .stdout
-                            .strip_suffix('\n')
-                            .unwrap_or(&command_result.stdout),
-                    );
-                }
+        let format = match &command_config.hover_format {
+            Some(format) if state.hover.as_ref().is_some_and(|h| h.key == name) => format,
+            _ => &command_config.format,
+        };
 
-                if content.contains("{stderr}") {
-                    content = content.replace(
-                        "{stderr}",
-                        command_result
-                            .stderr
-                            .strip_suffix('\n')
-                            .unwrap_or(&command_result.stderr),
-                    );
-                }
+        let content = format
+            .iter()
+            .filter_map(|f| {
+                let content = f.render_content(state, lookup)?;
 
-                (f, content)
+                Some((f, content))
             })
             .fold("".to_owned(), |acc, (f, content)| {
                 if command_config.render_mode == RenderMode::Static {
-                    return format!("{acc}{}", f.format_string(&content));
+                    let link = f.fill_link(lookup);
+
+                    return format!(
+                        "{acc}{}",
+                        f.format_string_with_link(&content, link.as_deref())
+                    );
                 }
 
                 format!("{acc}{}", content)
@@ -130,7 +180,7 @@ impl Widget for CommandWidget {
 
         match command_config.render_mode {
             RenderMode::Static => content,
-            RenderMode::Dynamic => render_dynamic_formatted_content(&content, &self.zj_conf),
+            RenderMode::Dynamic => render_dynamic_formatted_content(&content, &self.zj_conf, state),
             RenderMode::Raw => command_result
                 .stdout
                 .strip_suffix('\n')
@@ -139,7 +189,63 @@ impl Widget for CommandWidget {
         }
     }
 
-    fn process_click(&self, name: &str, _state: &ZellijState, _pos: usize) {
+    fn next_update(&self, state: &ZellijState) -> Option<DateTime<Local>> {
+        let now = Local::now();
+
+        // commands, that were just issued, are shown as running right away
+        if !self.started.lock().unwrap().is_empty() {
+            return Some(now);
+        }
+
+        self.config
+            .iter()
+            .filter_map(|(name, command_config)| {
+                let due = due_at(name, command_config, state)?;
+
+                // commands, that are due but not rendered, are checked again
+                // after their interval
+                match due > now {
+                    true => Some(due),
+                    false => Some(now + Duration::seconds(command_config.interval)),
+                }
+            })
+            .min()
+    }
+
+    /// Commands, whose interval passed, are rendered again in order to run
+    /// them. Commands, that were just issued, are rendered again to show them
+    /// as running.
+    fn expired(&self, state: &ZellijState) -> Vec<Dependency> {
+        let now = Local::now();
+        let started = std::mem::take(&mut *self.started.lock().unwrap());
+
+        self.config
+            .iter()
+            .filter(|(name, command_config)| {
+                started.contains(*name)
+                    || due_at(name, command_config, state).is_some_and(|due| due <= now)
+            })
+            .map(|(name, _)| Dependency::Command(name.to_owned()))
+            .collect()
+    }
+
+    fn runtime_state(&self) -> Option<Box<dyn Any>> {
+        Some(Box::new((
+            self.issued.lock().unwrap().clone(),
+            self.started.lock().unwrap().clone(),
+        )))
+    }
+
+    fn restore_runtime_state(&self, state: Box<dyn Any>) {
+        if let Ok(state) = state.downcast::<(BTreeMap<String, i64>, BTreeSet<String>)>() {
+            let (issued, started) = *state;
+
+            *self.issued.lock().unwrap() = issued;
+            *self.started.lock().unwrap() = started;
+        }
+    }
+
+    fn process_click(&self, name: &str, state: &ZellijState, _pos: usize, action: MouseAction) {
         let command_config = match self.config.get(name) {
             Some(cc) => cc,
             None => {
@@ -147,36 +253,86 @@ impl Widget for CommandWidget {
             }
         };
 
-        if command_config.click_action.is_empty() {
-            return;
+        if let Some(click_action) = command_config.click_actions.get(&action) {
+            click_action.run(state);
         }
+    }
 
-        let command = commandline_parser(&command_config.click_action);
-        let context: BTreeMap<String, String> = BTreeMap::new();
+    fn dependencies(&self, name: &str) -> Vec<Dependency> {
+        if let Some(name) = name.strip_suffix(RUNNING_SUFFIX) {
+            return vec![Dependency::Command(name.to_owned())];
+        }
 
-        tracing::debug!("Running command {:?} {:?}", command, context);
+        // commands are run from the render, once their interval passed, see
+        // `expired`
+        let mut dependencies = vec![Dependency::Command(name.to_owned()), Dependency::Mode];
 
-        #[cfg(all(not(feature = "bench"), not(test)))]
-        run_command(
-            &command.iter().map(|x| x.as_str()).collect::<Vec<&str>>(),
-            context,
-        );
+        if self
+            .config
+            .get(name)
+            .is_some_and(|command_config| command_config.hover_format.is_some())
+        {
+            dependencies.push(Dependency::Hover);
+        }
+
+        dependencies
+    }
+
+    fn tooltip(&self, name: &str, state: &ZellijState, _pos: usize) -> Option<String> {
+        let stdout = state.command_results.get(name)?.stdout.trim();
+
+        match stdout.is_empty() {
+            true => None,
+            false => Some(stdout.lines().map(str::trim).collect::<Vec<_>>().join(" ")),
+        }
+    }
+}
+
+/// Returns when the command has to be run again, based on the timestamp of
+/// its last result.
+fn due_at(
+    name: &str,
+    command_config: &CommandConfig,
+    state: &ZellijState,
+) -> Option<DateTime<Local>> {
+    if command_config.interval <= 0 {
+        return None;
     }
+
+    let timestamp = state.command_results.get(name)?.context.get("timestamp")?;
+    let last_run = DateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+
+    Some(last_run.with_timezone(&Local) + Duration::seconds(command_config.interval))
 }
 
-fn render_dynamic_formatted_content(content: &str, config: &BTreeMap<String, String>) -> String {
+fn render_dynamic_formatted_content(
+    content: &str,
+    config: &BTreeMap<String, String>,
+    state: &ZellijState,
+) -> String {
     formatted_parts_from_string_cached(content, config)
         .iter()
+        .filter(|fp| fp.conditions_hold(state, |_| None))
         .map(|fp| fp.format_string(&fp.content))
         .collect::<Vec<String>>()
         .join("")
 }
 
+/// Runs the command once its interval passed and returns the timestamp of the
+/// run, that is reported back in the context of its result.
 #[tracing::instrument(skip(command_config, state))]
-fn run_command_if_needed(command_config: CommandConfig, name: &str, state: &ZellijState) -> bool {
+fn run_command_if_needed(
+    command_config: &CommandConfig,
+    name: &str,
+    state: &ZellijState,
+) -> Option<DateTime<Local>> {
+    if state.background {
+        return None;
+    }
+
     let got_result = state.command_results.contains_key(name);
     if got_result && command_config.interval == 0 {
-        return false;
+        return None;
     }
 
     let ts = Local::now();
@@ -198,12 +354,12 @@ fn run_command_if_needed(command_config: CommandConfig, name: &str, state: &Zell
             #[cfg(all(not(feature = "bench"), not(test)))]
             run_command_with_env_variables_and_cwd(
                 &command.iter().map(|x| x.as_str()).collect::<Vec<&str>>(),
-                command_config.env.unwrap(),
-                command_config.cwd.unwrap(),
+                command_config.env.clone().unwrap(),
+                command_config.cwd.clone().unwrap(),
                 context,
             );
 
-            return true;
+            return Some(ts);
         }
 
         #[cfg(all(not(feature = "bench"), not(test)))]
@@ -212,10 +368,10 @@ fn run_command_if_needed(command_config: CommandConfig, name: &str, state: &Zell
             context,
         );
 
-        return true;
+        return Some(ts);
     }
 
-    false
+    None
 }
 
 fn parse_config(zj_conf: &BTreeMap<String, String>) -> BTreeMap<String, CommandConfig> {
@@ -229,16 +385,25 @@ fn parse_config(zj_conf: &BTreeMap<String, String>) -> BTreeMap<String, CommandC
     let mut config: BTreeMap<String, CommandConfig> = BTreeMap::new();
 
     for key in keys {
-        let command_name = COMMAND_REGEX.replace(&key, "").to_string();
+        let mouse_action = parse_mouse_action_key(&key);
+
+        let command_name = match mouse_action {
+            Some((name, _)) => name.to_owned(),
+            None => match key.strip_suffix(HOVER_FORMAT_KEY) {
+                Some(name) => name.to_owned(),
+                None => COMMAND_REGEX.replace(&key, "").to_string(),
+            },
+        };
 
         let mut command_conf = CommandConfig {
             command: "".to_owned(),
             format: Vec::new(),
+            hover_format: None,
             cwd: None,
             env: None,
             interval: 1,
             render_mode: RenderMode::Static,
-            click_action: "".to_owned(),
+            click_actions: BTreeMap::new(),
             hide_on_empty_stdout: false,
         };
 
@@ -252,10 +417,11 @@ fn parse_config(zj_conf: &BTreeMap<String, String>) -> BTreeMap<String, CommandC
                 .clone_from(&zj_conf.get(&key).unwrap().to_owned());
         }
 
-        if key.ends_with("clickaction") {
-            command_conf
-                .click_action
-                .clone_from(&zj_conf.get(&key).unwrap().to_owned());
+        // invalid actions are reported on load
+        if let Some((_, action)) = mouse_action
+            && let Ok(click_action) = zj_conf.get(&key).unwrap().parse::<ClickAction>()
+        {
+            command_conf.click_actions.insert(action, click_action);
         }
 
         if key.ends_with("env") {
@@ -278,6 +444,13 @@ fn parse_config(zj_conf: &BTreeMap<String, String>) -> BTreeMap<String, CommandC
                 FormattedPart::multiple_from_format_string(zj_conf.get(&key).unwrap(), zj_conf);
         }
 
+        if key.ends_with(HOVER_FORMAT_KEY) {
+            command_conf.hover_format = Some(FormattedPart::multiple_from_format_string(
+                zj_conf.get(&key).unwrap(),
+                zj_conf,
+            ));
+        }
+
         if key.ends_with("interval") {
             command_conf.interval = zj_conf.get(&key).unwrap().parse::<i64>().unwrap_or(1);
         }
@@ -334,7 +507,7 @@ fn get_timestamp_from_event_or_default(
 ) -> DateTime<Local> {
     let command_result = state.command_results.get(name);
     if command_result.is_none() {
-        if lock(name, state.clone()) {
+        if lock(name, state) {
             return Local::now();
         }
 
@@ -349,7 +522,7 @@ fn get_timestamp_from_event_or_default(
     let ts_context = ts_context.unwrap();
 
     if Local::now().timestamp() - state.start_time.timestamp() < interval {
-        release(name, state.clone());
+        release(name, state);
     }
 
     match DateTime::parse_from_str(ts_context, TIMESTAMP_FORMAT) {
@@ -358,7 +531,7 @@ fn get_timestamp_from_event_or_default(
     }
 }
 
-fn lock(name: &str, state: ZellijState) -> bool {
+fn lock(name: &str, state: &ZellijState) -> bool {
     let path = format!("/tmp/{}.{}.lock", state.plugin_uuid, name);
 
     if !Path::new(&path).exists() {
@@ -370,7 +543,7 @@ fn lock(name: &str, state: ZellijState) -> bool {
     true
 }
 
-fn release(name: &str, state: ZellijState) {
+fn release(name: &str, state: &ZellijState) {
     let path = format!("/tmp/{}.{}.lock", state.plugin_uuid, name);
 
     if Path::new(&path).exists() {
@@ -378,7 +551,7 @@ fn release(name: &str, state: ZellijState) {
     }
 }
 
-fn commandline_parser(input: &str) -> Vec<String> {
+pub fn commandline_parser(input: &str) -> Vec<String> {
     let mut output: Vec<String> = Vec::new();
 
     let special_chars = ['"', '\''];
@@ -433,6 +606,7 @@ fn commandline_parser(input: &str) -> Vec<String> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::action::ZellijAction;
     use rstest::rstest;
 
     #[test]
@@ -453,6 +627,120 @@ mod test {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    pub fn test_parse_config_mouse_actions() {
+        let config = parse_config(&BTreeMap::from([
+            ("command_git_command".to_owned(), "git status".to_owned()),
+            (
+                "command_git_clickaction".to_owned(),
+                "zellij:new_tab".to_owned(),
+            ),
+            (
+                "command_git_clickaction_right".to_owned(),
+                "right".to_owned(),
+            ),
+            ("command_git_scrolldown".to_owned(), "down".to_owned()),
+            ("command_git_format_hover".to_owned(), "{stdout}".to_owned()),
+        ]));
+
+        let command = config.get("command_git").unwrap();
+        assert_eq!(config.len(), 1);
+        assert_eq!(command.command, "git status");
+        assert_eq!(
+            command.click_actions,
+            BTreeMap::from([
+                (
+                    MouseAction::LeftClick,
+                    ClickAction::Zellij(ZellijAction::NewTab)
+                ),
+                (
+                    MouseAction::RightClick,
+                    ClickAction::Command(vec!["right".to_owned()])
+                ),
+                (
+                    MouseAction::ScrollDown,
+                    ClickAction::Command(vec!["down".to_owned()])
+                ),
+            ])
+        );
+        assert!(command.hover_format.is_some());
+    }
+
+    #[test]
+    fn test_dependencies_and_expired() {
+        let widget = CommandWidget::new(&BTreeMap::from([
+            ("command_a_command".to_owned(), "echo a".to_owned()),
+            ("command_a_interval".to_owned(), "1".to_owned()),
+            ("command_b_command".to_owned(), "echo b".to_owned()),
+            ("command_b_interval".to_owned(), "10".to_owned()),
+            ("command_b_format_hover".to_owned(), "{stdout}".to_owned()),
+        ]));
+
+        let result = |seconds_ago: i64| CommandResult {
+            context: BTreeMap::from([(
+                "timestamp".to_owned(),
+                (Local::now() - Duration::seconds(seconds_ago))
+                    .format(TIMESTAMP_FORMAT)
+                    .to_string(),
+            )]),
+            ..CommandResult::default()
+        };
+        let state = ZellijState {
+            command_results: BTreeMap::from([
+                ("command_a".to_owned(), result(5)),
+                ("command_b".to_owned(), result(5)),
+            ]),
+            ..ZellijState::default()
+        };
+
+        assert_eq!(
+            widget.expired(&state),
+            vec![Dependency::Command("command_a".to_owned())]
+        );
+        assert_eq!(
+            widget.dependencies("command_a"),
+            vec![
+                Dependency::Command("command_a".to_owned()),
+                Dependency::Mode
+            ]
+        );
+        assert!(
+            widget
+                .dependencies("command_b")
+                .contains(&Dependency::Hover)
+        );
+    }
+
+    #[test]
+    fn test_restore_runtime_state() {
+        let config = BTreeMap::from([("command_a_command".to_owned(), "echo a".to_owned())]);
+
+        let widget = CommandWidget::new(&config);
+        widget
+            .issued
+            .lock()
+            .unwrap()
+            .insert("command_a".to_owned(), Local::now().timestamp());
+        widget
+            .started
+            .lock()
+            .unwrap()
+            .insert("command_a".to_owned());
+
+        let state = ZellijState::default();
+        assert_eq!(widget.process("command_a.running", &state), "running");
+
+        let reloaded = CommandWidget::new(&config);
+        assert_eq!(reloaded.process("command_a.running", &state), "");
+
+        reloaded.restore_runtime_state(widget.runtime_state().unwrap());
+        assert_eq!(reloaded.process("command_a.running", &state), "running");
+        assert_eq!(
+            reloaded.expired(&state),
+            vec![Dependency::Command("command_a".to_owned())]
+        );
+    }
+
     #[rstest]
     // no result, interval 1 second
     #[case(1, &ZellijState::default(), true)]
@@ -488,25 +776,31 @@ mod test {
         )]),
         ..ZellijState::default()
     }, false)]
+    // do not run in instances of inactive tabs
+    #[case(1, &ZellijState {
+        background: true,
+        ..ZellijState::default()
+    }, false)]
     pub fn test_run_command_if_needed(
         #[case] interval: i64,
         #[case] state: &ZellijState,
         #[case] expected: bool,
     ) {
         let res = run_command_if_needed(
-            CommandConfig {
+            &CommandConfig {
                 command: "echo test".to_owned(),
                 format: Vec::new(),
+                hover_format: None,
                 env: None,
                 cwd: None,
                 interval,
                 render_mode: RenderMode::Static,
-                click_action: "".to_owned(),
+                click_actions: BTreeMap::new(),
                 hide_on_empty_stdout: false,
             },
             "test",
             state,
         );
-        assert_eq!(res, expected);
+        assert_eq!(res.is_some(), expected);
     }
 }
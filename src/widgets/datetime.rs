@@ -1,11 +1,14 @@
 use std::{collections::BTreeMap, str::FromStr};
 
-use chrono::Local;
+use chrono::{DateTime, Duration, Local, Timelike};
 use chrono_tz::Tz;
 
 use crate::render::FormattedPart;
 
-use crate::{config::ZellijState, widgets::widget::Widget};
+use crate::{
+    config::{Dependency, ZellijState},
+    widgets::widget::{MouseAction, Widget},
+};
 
 pub struct DateTimeWidget {
     format: String,
@@ -55,7 +58,7 @@ impl DateTimeWidget {
 }
 
 impl Widget for DateTimeWidget {
-    fn process(&self, _name: &str, _state: &ZellijState) -> String {
+    fn process(&self, _name: &str, state: &ZellijState) -> String {
         let date = Local::now();
 
         let mut tz = Tz::UTC;
@@ -65,45 +68,108 @@ impl Widget for DateTimeWidget {
 
         self.color_format
             .iter()
-            .map(|f| {
-                let mut content = f.content.clone();
-
-                if content.contains("{format}") {
-                    content = content.replace(
-                        "{format}",
-                        format!("{}", date.with_timezone(&tz).format(self.format.as_str()))
-                            .as_str(),
-                    );
-                }
-
-                if content.contains("{date}") {
-                    content = content.replace(
-                        "{date}",
-                        format!(
-                            "{}",
-                            date.with_timezone(&tz).format(self.date_format.as_str())
-                        )
-                        .as_str(),
-                    );
-                }
-
-                if content.contains("{time}") {
-                    content = content.replace(
-                        "{time}",
-                        format!(
-                            "{}",
-                            date.with_timezone(&tz).format(self.time_format.as_str())
-                        )
-                        .as_str(),
-                    );
-                }
-
-                (f, content)
+            .filter_map(|f| {
+                let content = f.render_content(state, |key| {
+                    let format = match key {
+                        "format" => &self.format,
+                        "date" => &self.date_format,
+                        "time" => &self.time_format,
+                        _ => return None,
+                    };
+
+                    Some(format!(
+                        "{}",
+                        date.with_timezone(&tz).format(format.as_str())
+                    ))
+                })?;
+
+                Some((f, content))
             })
             .fold("".to_owned(), |acc, (f, content)| {
                 format!("{acc}{}", f.format_string(&content))
             })
     }
 
-    fn process_click(&self, _name: &str, _state: &ZellijState, _pos: usize) {}
+    fn process_click(&self, _name: &str, _state: &ZellijState, _pos: usize, _action: MouseAction) {}
+
+    fn dependencies(&self, _name: &str) -> Vec<Dependency> {
+        vec![Dependency::Mode, Dependency::Clock]
+    }
+
+    fn next_update(&self, _state: &ZellijState) -> Option<DateTime<Local>> {
+        let with_seconds = [&self.format, &self.time_format, &self.date_format]
+            .iter()
+            .any(|format| shows_seconds(format));
+
+        next_tick(Local::now(), with_seconds)
+    }
+}
+
+/// Returns whether the strftime format contains a specifier with seconds,
+/// e.g. `%S`, `%T` or `%-S`.
+fn shows_seconds(format: &str) -> bool {
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+
+        let specifier = chars
+            .by_ref()
+            .find(|c| !matches!(c, '-' | '_' | '0'..='9' | '.' | ':' | '#'));
+
+        if matches!(
+            specifier,
+            Some('S' | 's' | 'T' | 'X' | 'r' | 'c' | '+' | 'f')
+        ) {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Returns the start of the next second or minute, so that the clock ticks
+/// in line with the wall clock.
+fn next_tick(now: DateTime<Local>, with_seconds: bool) -> Option<DateTime<Local>> {
+    let now = now.with_nanosecond(0)?;
+
+    match with_seconds {
+        true => Some(now + Duration::seconds(1)),
+        false => Some(now.with_second(0)? + Duration::minutes(1)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("%H:%M", false)]
+    #[case("%Y-%m-%d %%S", false)]
+    #[case("%H:%M:%S", true)]
+    #[case("%-S", true)]
+    #[case("%T", true)]
+    #[case("%.3f", true)]
+    fn test_shows_seconds(#[case] format: &str, #[case] expected: bool) {
+        assert_eq!(shows_seconds(format), expected);
+    }
+
+    #[test]
+    fn test_next_tick() {
+        let now =
+            Local.with_ymd_and_hms(2024, 1, 1, 12, 30, 15).unwrap() + Duration::milliseconds(250);
+
+        assert_eq!(
+            next_tick(now, true),
+            Some(Local.with_ymd_and_hms(2024, 1, 1, 12, 30, 16).unwrap())
+        );
+        assert_eq!(
+            next_tick(now, false),
+            Some(Local.with_ymd_and_hms(2024, 1, 1, 12, 31, 0).unwrap())
+        );
+    }
 }
@@ -4,6 +4,8 @@ pub mod mode;
 pub mod notification;
 pub mod pipe;
 pub mod session;
+pub mod spinner;
 pub mod swap_layout;
 pub mod tabs;
+pub mod tooltip;
 pub mod widget;
@@ -2,9 +2,12 @@ use std::collections::BTreeMap;
 
 use zellij_tile::prelude::InputMode;
 
-use crate::{config::ZellijState, render::FormattedPart};
+use crate::{
+    config::{Dependency, ZellijState},
+    render::FormattedPart,
+};
 
-use super::widget::Widget;
+use super::widget::{MouseAction, Widget};
 
 #[derive(Debug)]
 pub struct ModeWidget {
@@ -123,23 +126,24 @@ impl Widget for ModeWidget {
     fn process(&self, _name: &str, state: &ZellijState) -> String {
         self.select_format(state.mode.mode)
             .iter()
-            .map(|f| {
-                let mut content = f.content.clone();
+            .filter_map(|f| {
+                let content = f.render_content(state, |key| match key {
+                    "name" => Some(format!("{:?}", state.mode.mode)),
+                    _ => None,
+                })?;
 
-                if f.content.contains("{name}") {
-                    content = f
-                        .content
-                        .replace("{name}", format!("{:?}", state.mode.mode).as_str());
-                }
-
-                (f, content)
+                Some((f, content))
             })
             .fold("".to_owned(), |acc, (f, content)| {
                 format!("{acc}{}", f.format_string(&content))
             })
     }
 
-    fn process_click(&self, _name: &str, _state: &ZellijState, _pos: usize) {}
+    fn process_click(&self, _name: &str, _state: &ZellijState, _pos: usize, _action: MouseAction) {}
+
+    fn dependencies(&self, _name: &str) -> Vec<Dependency> {
+        vec![Dependency::Mode]
+    }
 }
 
 impl ModeWidget {
@@ -187,7 +191,7 @@ impl ModeWidget {
     }
 }
 
-fn map_string_to_mode(s: &str) -> Option<InputMode> {
+pub fn map_string_to_mode(s: &str) -> Option<InputMode> {
     match s {
         "normal" => Some(InputMode::Normal),
         "locked" => Some(InputMode::Locked),
@@ -1,9 +1,12 @@
 use std::collections::BTreeMap;
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, TimeZone};
 
 use crate::render::FormattedPart;
-use crate::{config::ZellijState, widgets::widget::Widget};
+use crate::{
+    config::{Dependency, ZellijState},
+    widgets::widget::{MouseAction, Widget},
+};
 
 #[derive(Clone, Debug, Default)]
 pub struct Message {
@@ -55,18 +58,20 @@ impl Widget for NotificationWidget {
         tracing::debug!("no_new: {}", no_new);
 
         let format = match no_new {
-            true => self.format_no_notifications.clone(),
-            false => self.format_unread.clone(),
+            true => &self.format_no_notifications,
+            false => &self.format_unread,
         };
 
         let mut output = "".to_owned();
 
         for f in format.iter() {
-            let mut content = f.content.clone();
-
-            if content.contains("{message}") {
-                content = content.replace("{message}", message.body.as_str());
-            }
+            let content = match f.render_content(state, |key| match key {
+                "message" => Some(message.body.clone()),
+                _ => None,
+            }) {
+                Some(content) => content,
+                None => continue,
+            };
 
             output = format!("{}{}", output, f.format_string(&content));
         }
@@ -74,5 +79,31 @@ impl Widget for NotificationWidget {
         output.to_owned()
     }
 
-    fn process_click(&self, _name: &str, _state: &ZellijState, _pos: usize) {}
+    fn process_click(&self, _name: &str, _state: &ZellijState, _pos: usize, _action: MouseAction) {}
+
+    fn dependencies(&self, _name: &str) -> Vec<Dependency> {
+        vec![
+            Dependency::Notifications,
+            Dependency::Mode,
+            Dependency::Clock,
+        ]
+    }
+
+    fn tooltip(&self, _name: &str, state: &ZellijState, _pos: usize) -> Option<String> {
+        state
+            .incoming_notification
+            .as_ref()
+            .map(|message| message.body.clone())
+            .filter(|body| !body.is_empty())
+    }
+
+    fn next_update(&self, state: &ZellijState) -> Option<DateTime<Local>> {
+        let message = state.incoming_notification.as_ref()?;
+        let expires_at = message.received_at.timestamp() + self.show_interval + 1;
+
+        match expires_at > Local::now().timestamp() {
+            true => Local.timestamp_opt(expires_at, 0).single(),
+            false => None,
+        }
+    }
 }
@@ -2,9 +2,10 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::BTreeMap;
 
+use crate::config::{Dependency, ZellijState};
 use crate::render::{FormattedPart, formatted_parts_from_string_cached};
 
-use super::widget::Widget;
+use super::widget::{MouseAction, Widget};
 
 lazy_static! {
     static ref PIPE_REGEX: Regex = Regex::new("_[a-zA-Z0-9]+$").unwrap();
@@ -38,7 +39,7 @@ impl PipeWidget {
 }
 
 impl Widget for PipeWidget {
-    fn process(&self, name: &str, state: &crate::config::ZellijState) -> String {
+    fn process(&self, name: &str, state: &ZellijState) -> String {
         let pipe_config = match self.config.get(name) {
             Some(pc) => pc,
             None => {
@@ -58,17 +59,18 @@ impl Widget for PipeWidget {
         let content = pipe_config
             .format
             .iter()
-            .map(|f| {
-                let mut content = f.content.clone();
-
-                if content.contains("{output}") {
-                    content = content.replace(
-                        "{output}",
-                        pipe_result.strip_suffix('\n').unwrap_or(pipe_result),
-                    )
-                }
-
-                (f, content)
+            .filter_map(|f| {
+                let content = f.render_content(state, |key| match key {
+                    "output" => Some(
+                        pipe_result
+                            .strip_suffix('\n')
+                            .unwrap_or(pipe_result)
+                            .to_owned(),
+                    ),
+                    _ => None,
+                })?;
+
+                Some((f, content))
             })
             .fold("".to_owned(), |acc, (f, content)| {
                 if pipe_config.render_mode == RenderMode::Static {
@@ -80,17 +82,26 @@ impl Widget for PipeWidget {
 
         match pipe_config.render_mode {
             RenderMode::Static => content,
-            RenderMode::Dynamic => render_dynamic_formatted_content(&content, &self.zj_conf),
+            RenderMode::Dynamic => render_dynamic_formatted_content(&content, &self.zj_conf, state),
             RenderMode::Raw => pipe_result.to_owned(),
         }
     }
 
-    fn process_click(&self, _name: &str, _state: &crate::config::ZellijState, _pos: usize) {}
+    fn process_click(&self, _name: &str, _state: &ZellijState, _pos: usize, _action: MouseAction) {}
+
+    fn dependencies(&self, name: &str) -> Vec<Dependency> {
+        vec![Dependency::Pipe(name.to_owned()), Dependency::Mode]
+    }
 }
 
-fn render_dynamic_formatted_content(content: &str, config: &BTreeMap<String, String>) -> String {
+fn render_dynamic_formatted_content(
+    content: &str,
+    config: &BTreeMap<String, String>,
+    state: &ZellijState,
+) -> String {
     formatted_parts_from_string_cached(content, config)
         .iter()
+        .filter(|fp| fp.conditions_hold(state, |_| None))
         .map(|fp| fp.format_string(&fp.content))
         .collect::<Vec<String>>()
         .join("")
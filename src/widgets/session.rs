@@ -1,6 +1,11 @@
 use std::collections::BTreeMap;
 
-use crate::{config::ZellijState, widgets::widget::Widget};
+use zellij_tile::{prelude::SessionInfo, shim::switch_session};
+
+use crate::{
+    config::{Dependency, ZellijState},
+    widgets::widget::{MouseAction, Widget},
+};
 
 pub struct SessionWidget {}
 
@@ -18,5 +23,66 @@ impl Widget for SessionWidget {
         }
     }
 
-    fn process_click(&self, _name: &str, _state: &ZellijState, _pos: usize) {}
+    /// Scrolling switches to the previous or next session by name.
+    fn process_click(&self, _name: &str, state: &ZellijState, _pos: usize, action: MouseAction) {
+        let forward = match action {
+            MouseAction::ScrollUp => false,
+            MouseAction::ScrollDown => true,
+            _ => return,
+        };
+
+        let Some(session) = neighbour_session(&state.sessions, forward) else {
+            return;
+        };
+
+        switch_session(Some(&session));
+    }
+
+    fn dependencies(&self, _name: &str) -> Vec<Dependency> {
+        vec![Dependency::Mode]
+    }
+}
+
+/// Returns the name of the session before or after the current one, sorted
+/// by name and wrapping around at the ends.
+fn neighbour_session(sessions: &[SessionInfo], forward: bool) -> Option<String> {
+    let mut names: Vec<&str> = sessions.iter().map(|s| s.name.as_str()).collect();
+    names.sort();
+
+    let current = sessions.iter().find(|s| s.is_current_session)?;
+    let index = names.iter().position(|name| *name == current.name)?;
+
+    let index = match forward {
+        true => (index + 1) % names.len(),
+        false => (index + names.len() - 1) % names.len(),
+    };
+
+    match names[index] == current.name {
+        true => None,
+        false => Some(names[index].to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_neighbour_session() {
+        let session = |name: &str, current: bool| SessionInfo {
+            name: name.to_owned(),
+            is_current_session: current,
+            ..Default::default()
+        };
+
+        let sessions = vec![session("b", true), session("c", false), session("a", false)];
+
+        assert_eq!(neighbour_session(&sessions, true), Some("c".to_owned()));
+        assert_eq!(neighbour_session(&sessions, false), Some("a".to_owned()));
+
+        let sessions = vec![session("a", false), session("c", true)];
+        assert_eq!(neighbour_session(&sessions, true), Some("a".to_owned()));
+
+        assert_eq!(neighbour_session(&[session("a", true)], true), None);
+    }
 }
@@ -0,0 +1,133 @@
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use chrono::{DateTime, Duration, Local};
+
+use crate::{
+    config::{Dependency, ZellijState},
+    widgets::widget::{MouseAction, Widget},
+};
+
+/// Default time between two frames in milliseconds.
+const DEFAULT_INTERVAL: u64 = 100;
+
+/// Frame sets, that can be used without configuring them, e.g. `{spinner_line}`.
+const BUILTIN_FRAMES: [(&str, &str); 4] = [
+    ("dots", "⠋ ⠙ ⠹ ⠸ ⠼ ⠴ ⠦ ⠧ ⠇ ⠏"),
+    ("line", "- \\ | /"),
+    ("arc", "◜ ◠ ◝ ◞ ◡ ◟"),
+    ("bounce", "⠁ ⠂ ⠄ ⠂"),
+];
+
+/// Renders an animated placeholder like `{spinner}` or `{spinner_<name>}`.
+/// The frames are configured as a whitespace separated list with
+/// `spinner_frames` or `spinner_<name>_frames` and advance every
+/// `spinner_interval` milliseconds.
+pub struct SpinnerWidget {
+    frames: BTreeMap<String, Vec<String>>,
+    interval: u64,
+    visible: AtomicBool,
+}
+
+impl SpinnerWidget {
+    pub fn new(config: &BTreeMap<String, String>) -> Self {
+        let mut frames: BTreeMap<String, Vec<String>> = BUILTIN_FRAMES
+            .iter()
+            .map(|(name, frames)| (format!("spinner_{name}"), parse_frames(frames)))
+            .collect();
+
+        if let Some(dots) = frames.get("spinner_dots").cloned() {
+            frames.insert("spinner".to_owned(), dots);
+        }
+
+        for (key, value) in config {
+            let name = match key.strip_suffix("_frames") {
+                Some(name) if name.starts_with("spinner") => name,
+                _ => continue,
+            };
+
+            let value = parse_frames(value);
+            if !value.is_empty() {
+                frames.insert(name.to_owned(), value);
+            }
+        }
+
+        let interval = match config.get("spinner_interval") {
+            Some(interval) => interval.parse::<u64>().unwrap_or(DEFAULT_INTERVAL),
+            None => DEFAULT_INTERVAL,
+        };
+
+        Self {
+            frames,
+            interval: interval.clamp(20, 500),
+            visible: AtomicBool::new(false),
+        }
+    }
+
+    fn frame(&self, name: &str, millis: i64) -> &str {
+        let frames = match self.frames.get(name) {
+            Some(frames) => frames,
+            None => return "",
+        };
+
+        let index = (millis.unsigned_abs() / self.interval) as usize % frames.len();
+
+        &frames[index]
+    }
+}
+
+impl Widget for SpinnerWidget {
+    fn process(&self, name: &str, _state: &ZellijState) -> String {
+        self.visible.store(true, Ordering::Relaxed);
+
+        self.frame(name, Local::now().timestamp_millis()).to_owned()
+    }
+
+    fn process_click(&self, _name: &str, _state: &ZellijState, _pos: usize, _action: MouseAction) {}
+
+    fn dependencies(&self, _name: &str) -> Vec<Dependency> {
+        vec![Dependency::Clock]
+    }
+
+    /// Requests the next frame, as long as a spinner has been rendered since
+    /// the last call.
+    fn next_update(&self, _state: &ZellijState) -> Option<DateTime<Local>> {
+        match self.visible.swap(false, Ordering::Relaxed) {
+            true => Some(Local::now() + Duration::milliseconds(self.interval as i64)),
+            false => None,
+        }
+    }
+}
+
+fn parse_frames(frames: &str) -> Vec<String> {
+    frames.split_whitespace().map(|f| f.to_owned()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_spinner_frames() {
+        let config = BTreeMap::from([
+            ("spinner_build_frames".to_owned(), "a b  c".to_owned()),
+            ("spinner_interval".to_owned(), "200".to_owned()),
+        ]);
+
+        let spinner = SpinnerWidget::new(&config);
+
+        assert_eq!(spinner.frame("spinner", 0), "⠋");
+        assert_eq!(spinner.frame("spinner_line", 400), "|");
+        assert_eq!(spinner.frame("spinner_build", 200), "b");
+        assert_eq!(spinner.frame("spinner_build", 1000), "c");
+        assert_eq!(spinner.frame("spinner_unknown", 0), "");
+
+        let state = ZellijState::default();
+        assert!(spinner.next_update(&state).is_none());
+        spinner.process("spinner", &state);
+        assert!(spinner.next_update(&state).is_some());
+        assert!(spinner.next_update(&state).is_none());
+    }
+}
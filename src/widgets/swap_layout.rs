@@ -1,8 +1,11 @@
 use std::collections::BTreeMap;
-use zellij_tile::shim::next_swap_layout;
+use zellij_tile::shim::{next_swap_layout, previous_swap_layout};
 
 use crate::render::FormattedPart;
-use crate::{config::ZellijState, widgets::widget::Widget};
+use crate::{
+    config::{Dependency, ZellijState},
+    widgets::widget::{MouseAction, Widget},
+};
 
 pub struct SwapLayoutWidget {
     format: Vec<FormattedPart>,
@@ -54,11 +57,13 @@ impl Widget for SwapLayoutWidget {
         let mut output = "".to_owned();
 
         for f in &self.format {
-            let mut content = f.content.clone();
-
-            if content.contains("{name}") {
-                content = content.replace("{name}", &name);
-            }
+            let content = match f.render_content(state, |key| match key {
+                "name" => Some(name.clone()),
+                _ => None,
+            }) {
+                Some(content) => content,
+                None => continue,
+            };
 
             output = format!("{}{}", output, f.format_string(&content));
         }
@@ -66,7 +71,14 @@ impl Widget for SwapLayoutWidget {
         output
     }
 
-    fn process_click(&self, _name: &str, _state: &ZellijState, _pos: usize) {
-        next_swap_layout()
+    fn process_click(&self, _name: &str, _state: &ZellijState, _pos: usize, action: MouseAction) {
+        match action {
+            MouseAction::LeftClick | MouseAction::ScrollDown => next_swap_layout(),
+            MouseAction::RightClick | MouseAction::ScrollUp => previous_swap_layout(),
+        }
+    }
+
+    fn dependencies(&self, _name: &str) -> Vec<Dependency> {
+        vec![Dependency::Tabs, Dependency::Mode]
     }
 }
@@ -1,13 +1,16 @@
-use std::{cmp, collections::BTreeMap};
+use std::{any::Any, cmp, collections::BTreeMap, sync::Mutex};
 
 use zellij_tile::{
     prelude::{InputMode, ModeInfo, PaneInfo, PaneManifest, TabInfo},
-    shim::switch_tab_to,
+    shim::{go_to_next_tab, go_to_previous_tab, switch_tab_to},
 };
 
-use crate::{config::ZellijState, render::FormattedPart};
+use crate::{
+    config::{Dependency, ZellijState},
+    render::{FormattedPart, measure_text_width},
+};
 
-use super::widget::Widget;
+use super::widget::{MouseAction, Widget};
 
 pub struct TabsWidget {
     active_tab_format: Vec<FormattedPart>,
@@ -18,6 +21,8 @@ pub struct TabsWidget {
     normal_tab_sync_format: Vec<FormattedPart>,
     normal_tab_bell_format: Option<Vec<FormattedPart>>,
     normal_tab_flashing_bell_format: Option<Vec<FormattedPart>>,
+    normal_tab_hover_format: Option<Vec<FormattedPart>>,
+    active_tab_hover_format: Option<Vec<FormattedPart>>,
     rename_tab_format: Vec<FormattedPart>,
     separator: Option<FormattedPart>,
     fullscreen_indicator: Option<String>,
@@ -29,6 +34,9 @@ pub struct TabsWidget {
     tab_truncate_start_format: Vec<FormattedPart>,
     tab_truncate_end_format: Vec<FormattedPart>,
     tab_zero_based_index: bool,
+    // position of the tab, that has been rendered with its hover format in the
+    // last output
+    rendered_hover: Mutex<Option<usize>>,
 }
 
 impl TabsWidget {
@@ -71,6 +79,14 @@ impl TabsWidget {
             None => active_tab_format.clone(),
         };
 
+        let normal_tab_hover_format = config
+            .get("tab_normal_hover")
+            .map(|form| FormattedPart::multiple_from_format_string(form, config));
+
+        let active_tab_hover_format = config
+            .get("tab_active_hover")
+            .map(|form| FormattedPart::multiple_from_format_string(form, config));
+
         let rename_tab_format = match config.get("tab_rename") {
             Some(form) => FormattedPart::multiple_from_format_string(form, config),
             None => active_tab_format.clone(),
@@ -112,6 +128,8 @@ impl TabsWidget {
             normal_tab_sync_format,
             normal_tab_bell_format,
             normal_tab_flashing_bell_format,
+            normal_tab_hover_format,
+            active_tab_hover_format,
             active_tab_format,
             active_tab_fullscreen_format,
             active_tab_sync_format,
@@ -126,32 +144,38 @@ impl TabsWidget {
             tab_truncate_start_format,
             tab_truncate_end_format,
             tab_zero_based_index,
+            rendered_hover: Mutex::new(None),
         }
     }
 }
 
 impl Widget for TabsWidget {
-    fn process(&self, _name: &str, state: &ZellijState) -> String {
+    fn process(&self, name: &str, state: &ZellijState) -> String {
         let mut output = "".to_owned();
         let mut counter = 0;
 
+        let hovered = self.hovered_tab(name, state);
+        *self.rendered_hover.lock().unwrap() = hovered;
+
         let (truncated_start, truncated_end, tabs) =
             get_tab_window(&state.tabs, self.tab_display_count);
 
         if truncated_start > 0 {
             for f in &self.tab_truncate_start_format {
-                let mut content = f.content.clone();
-
-                if content.contains("{count}") {
-                    content = content.replace("{count}", (truncated_start).to_string().as_str());
-                }
+                let content = match f.render_content(state, |key| match key {
+                    "count" => Some(truncated_start.to_string()),
+                    _ => None,
+                }) {
+                    Some(content) => content,
+                    None => continue,
+                };
 
                 output = format!("{output}{}", f.format_string(&content));
             }
         }
 
         for tab in &tabs {
-            let content = self.render_tab(tab, &state.panes, &state.mode);
+            let content = self.render_tab(tab, state, hovered == Some(tab.position));
             counter += 1;
 
             output = format!("{}{}", output, content);
@@ -165,11 +189,13 @@ impl Widget for TabsWidget {
 
         if truncated_end > 0 {
             for f in &self.tab_truncate_end_format {
-                let mut content = f.content.clone();
-
-                if content.contains("{count}") {
-                    content = content.replace("{count}", (truncated_end).to_string().as_str());
-                }
+                let content = match f.render_content(state, |key| match key {
+                    "count" => Some(truncated_end.to_string()),
+                    _ => None,
+                }) {
+                    Some(content) => content,
+                    None => continue,
+                };
 
                 output = format!("{output}{}", f.format_string(&content));
             }
@@ -178,7 +204,20 @@ impl Widget for TabsWidget {
         output
     }
 
-    fn process_click(&self, _name: &str, state: &ZellijState, pos: usize) {
+    fn process_click(&self, _name: &str, state: &ZellijState, pos: usize, action: MouseAction) {
+        match action {
+            MouseAction::LeftClick => {}
+            MouseAction::ScrollUp => {
+                go_to_previous_tab();
+                return;
+            }
+            MouseAction::ScrollDown => {
+                go_to_next_tab();
+                return;
+            }
+            _ => return,
+        }
+
         let mut offset = 0;
         let mut counter = 0;
 
@@ -195,13 +234,15 @@ impl Widget for TabsWidget {
 
         if truncated_start > 0 {
             for f in &self.tab_truncate_start_format {
-                let mut content = f.content.clone();
-
-                if content.contains("{count}") {
-                    content = content.replace("{count}", (truncated_end).to_string().as_str());
-                }
+                let content = match f.render_content(state, |key| match key {
+                    "count" => Some(truncated_end.to_string()),
+                    _ => None,
+                }) {
+                    Some(content) => content,
+                    None => continue,
+                };
 
-                offset += console::measure_text_width(&f.format_string(&content));
+                offset += measure_text_width(&f.format_string(&content));
 
                 if pos <= offset {
                     switch_tab_to(active_pos.saturating_sub(1) as u32);
@@ -209,10 +250,12 @@ impl Widget for TabsWidget {
             }
         }
 
+        let hovered = *self.rendered_hover.lock().unwrap();
+
         for tab in &tabs {
             counter += 1;
 
-            let mut rendered_content = self.render_tab(tab, &state.panes, &state.mode);
+            let mut rendered_content = self.render_tab(tab, state, hovered == Some(tab.position));
 
             if counter < tabs.len()
                 && let Some(sep) = &self.separator
@@ -221,7 +264,7 @@ impl Widget for TabsWidget {
                     format!("{}{}", rendered_content, sep.format_string(&sep.content));
             }
 
-            let content_len = console::measure_text_width(&rendered_content);
+            let content_len = measure_text_width(&rendered_content);
 
             if pos > offset && pos < offset + content_len {
                 switch_tab_to(tab.position as u32 + 1);
@@ -234,13 +277,15 @@ impl Widget for TabsWidget {
 
         if truncated_end > 0 {
             for f in &self.tab_truncate_end_format {
-                let mut content = f.content.clone();
-
-                if content.contains("{count}") {
-                    content = content.replace("{count}", (truncated_end).to_string().as_str());
-                }
+                let content = match f.render_content(state, |key| match key {
+                    "count" => Some(truncated_end.to_string()),
+                    _ => None,
+                }) {
+                    Some(content) => content,
+                    None => continue,
+                };
 
-                offset += console::measure_text_width(&f.format_string(&content));
+                offset += measure_text_width(&f.format_string(&content));
 
                 if pos <= offset {
                     switch_tab_to(cmp::min(active_pos + 1, state.tabs.len()) as u32);
@@ -248,6 +293,29 @@ impl Widget for TabsWidget {
             }
         }
     }
+
+    fn dependencies(&self, _name: &str) -> Vec<Dependency> {
+        vec![
+            Dependency::Tabs,
+            Dependency::Panes,
+            Dependency::Mode,
+            Dependency::Hover,
+        ]
+    }
+
+    fn runtime_state(&self) -> Option<Box<dyn Any>> {
+        Some(Box::new(*self.rendered_hover.lock().unwrap()))
+    }
+
+    fn restore_runtime_state(&self, state: Box<dyn Any>) {
+        if let Ok(hovered) = state.downcast::<Option<usize>>() {
+            *self.rendered_hover.lock().unwrap() = *hovered;
+        }
+    }
+
+    fn tooltip(&self, _name: &str, state: &ZellijState, pos: usize) -> Option<String> {
+        self.tab_at(state, pos).map(|tab| tab.name)
+    }
 }
 
 impl TabsWidget {
@@ -296,44 +364,119 @@ impl TabsWidget {
         &self.normal_tab_format
     }
 
-    fn render_tab(&self, tab: &TabInfo, panes: &PaneManifest, mode: &ModeInfo) -> String {
-        let formatters = self.select_format(tab, mode);
+    /// Returns the position of the tab under the cursor, if the widget is
+    /// hovered and a hover format is configured.
+    fn hovered_tab(&self, name: &str, state: &ZellijState) -> Option<usize> {
+        if self.normal_tab_hover_format.is_none() && self.active_tab_hover_format.is_none() {
+            return None;
+        }
+
+        let hover = state.hover.as_ref().filter(|h| h.key == name)?;
+
+        self.tab_at(state, hover.pos).map(|tab| tab.position)
+    }
+
+    /// Returns the tab at the offset into the last output. The tab, that has
+    /// been hovered in it, is measured with its hover format.
+    fn tab_at(&self, state: &ZellijState, pos: usize) -> Option<TabInfo> {
+        let hovered = *self.rendered_hover.lock().unwrap();
+        let (truncated_start, _, tabs) = get_tab_window(&state.tabs, self.tab_display_count);
+
+        let mut offset = 0;
+        if truncated_start > 0 {
+            for f in &self.tab_truncate_start_format {
+                if let Some(content) = f.render_content(state, |key| match key {
+                    "count" => Some(truncated_start.to_string()),
+                    _ => None,
+                }) {
+                    offset += measure_text_width(&f.format_string(&content));
+                }
+            }
+        }
+
+        for (i, tab) in tabs.iter().enumerate() {
+            let rendered_tab = self.render_tab(tab, state, hovered == Some(tab.position));
+            let mut content_len = measure_text_width(&rendered_tab);
+
+            if i + 1 < tabs.len()
+                && let Some(sep) = &self.separator
+            {
+                content_len += measure_text_width(&sep.format_string(&sep.content));
+            }
+
+            if pos >= offset && pos < offset + content_len {
+                return Some(tab.clone());
+            }
+
+            offset += content_len;
+        }
+
+        None
+    }
+
+    fn render_tab(&self, tab: &TabInfo, state: &ZellijState, hovered: bool) -> String {
+        let hover_format = match tab.active {
+            true => self.active_tab_hover_format.as_ref(),
+            false => self.normal_tab_hover_format.as_ref(),
+        };
+
+        let formatters = match hover_format {
+            Some(fmt) if hovered && !(tab.active && state.mode.mode == InputMode::RenameTab) => fmt,
+            _ => self.select_format(tab, &state.mode),
+        };
         let mut output = "".to_owned();
 
         for f in formatters.iter() {
-            let mut content = f.content.clone();
-
-            let tab_name = match mode.mode {
-                InputMode::RenameTab => match tab.name.is_empty() {
-                    true => "Enter name...",
-                    false => tab.name.as_str(),
-                },
-                _name => tab.name.as_str(),
+            let content = match f.render_content(state, |key| {
+                self.tab_placeholder(key, tab, &state.panes, &state.mode)
+            }) {
+                Some(content) => content,
+                None => continue,
             };
 
-            if content.contains("{name}") {
-                content = content.replace("{name}", tab_name);
-            }
+            output = format!("{}{}", output, f.format_string(&content));
+        }
 
-            if content.contains("{index}") {
+        output.to_owned()
+    }
+
+    fn tab_placeholder(
+        &self,
+        key: &str,
+        tab: &TabInfo,
+        panes: &PaneManifest,
+        mode: &ModeInfo,
+    ) -> Option<String> {
+        match key {
+            "name" => {
+                let tab_name = match mode.mode {
+                    InputMode::RenameTab => match tab.name.is_empty() {
+                        true => "Enter name...",
+                        false => tab.name.as_str(),
+                    },
+                    _name => tab.name.as_str(),
+                };
+
+                Some(tab_name.to_owned())
+            }
+            "index" => {
                 let index = match self.tab_zero_based_index {
                     true => tab.position,
                     false => tab.position + 1,
                 };
-                content = content.replace("{index}", index.to_string().as_str());
-            }
 
-            if content.contains("{floating_total_count}") {
+                Some(index.to_string())
+            }
+            "floating_total_count" => {
                 let panes_for_tab: Vec<PaneInfo> =
                     panes.panes.get(&tab.position).cloned().unwrap_or_default();
 
-                content = content.replace(
-                    "{floating_total_count}",
-                    &format!("{}", panes_for_tab.iter().filter(|p| p.is_floating).count()),
-                );
+                Some(format!(
+                    "{}",
+                    panes_for_tab.iter().filter(|p| p.is_floating).count()
+                ))
             }
-
-            if content.contains("{focused_pane_title}") {
+            "focused_pane_title" => {
                 let panes_for_tab: Vec<PaneInfo> =
                     panes.panes.get(&tab.position).cloned().unwrap_or_default();
 
@@ -343,78 +486,60 @@ impl TabsWidget {
                     .map(|pane| pane.title.clone())
                     .unwrap_or_default();
 
-                content = content.replace("{focused_pane_title}", &focused_pane_title);
+                Some(focused_pane_title)
             }
-
-            content = self.replace_indicators(content, tab, panes);
-
-            output = format!("{}{}", output, f.format_string(&content));
+            _ => self.indicator(key, tab, panes),
         }
-
-        output.to_owned()
     }
 
-    fn replace_indicators(&self, content: String, tab: &TabInfo, panes: &PaneManifest) -> String {
-        let mut content = content;
-        if content.contains("{fullscreen_indicator}")
-            && let Some(fullscreen_indicator) = self.fullscreen_indicator.clone()
-        {
-            content = content.replace(
-                "{fullscreen_indicator}",
-                if tab.is_fullscreen_active {
-                    fullscreen_indicator.as_ref()
-                } else {
-                    ""
-                },
-            );
-        }
+    fn indicator(&self, key: &str, tab: &TabInfo, panes: &PaneManifest) -> Option<String> {
+        match key {
+            "fullscreen_indicator" => {
+                let fullscreen_indicator = self.fullscreen_indicator.clone()?;
 
-        if content.contains("{sync_indicator}")
-            && let Some(sync_indicator) = self.sync_indicator.clone()
-        {
-            content = content.replace(
-                "{sync_indicator}",
-                if tab.is_sync_panes_active {
-                    sync_indicator.as_ref()
-                } else {
-                    ""
-                },
-            );
-        }
+                Some(match tab.is_fullscreen_active {
+                    true => fullscreen_indicator,
+                    false => "".to_owned(),
+                })
+            }
+            "sync_indicator" => {
+                let sync_indicator = self.sync_indicator.clone()?;
 
-        if content.contains("{floating_indicator}")
-            && let Some(floating_indicator) = self.floating_indicator.clone()
-        {
-            let panes_for_tab: Vec<PaneInfo> =
-                panes.panes.get(&tab.position).cloned().unwrap_or_default();
+                Some(match tab.is_sync_panes_active {
+                    true => sync_indicator,
+                    false => "".to_owned(),
+                })
+            }
+            "floating_indicator" => {
+                let floating_indicator = self.floating_indicator.clone()?;
 
-            let is_floating = panes_for_tab.iter().any(|p| p.is_floating);
+                let panes_for_tab: Vec<PaneInfo> =
+                    panes.panes.get(&tab.position).cloned().unwrap_or_default();
 
-            content = content.replace(
-                "{floating_indicator}",
-                if is_floating {
-                    floating_indicator.as_ref()
+                let is_floating = panes_for_tab.iter().any(|p| p.is_floating);
+
+                Some(match is_floating {
+                    true => floating_indicator,
+                    false => "".to_owned(),
+                })
+            }
+            "bell_indicator" => {
+                if self.bell_indicator.is_none() && self.flashing_bell_indicator.is_none() {
+                    return None;
+                }
+
+                let indicator = if tab.is_flashing_bell {
+                    self.flashing_bell_indicator.as_deref().unwrap_or("")
+                } else if tab.has_bell_notification {
+                    self.bell_indicator.as_deref().unwrap_or("")
                 } else {
                     ""
-                },
-            );
-        }
-
-        if content.contains("{bell_indicator}")
-            && (self.bell_indicator.is_some() || self.flashing_bell_indicator.is_some())
-        {
-            let indicator = if tab.is_flashing_bell {
-                self.flashing_bell_indicator.as_deref().unwrap_or("")
-            } else if tab.has_bell_notification {
-                self.bell_indicator.as_deref().unwrap_or("")
-            } else {
-                ""
-            };
+                };
 
-            content = content.replace("{bell_indicator}", indicator);
+                Some(indicator.to_owned())
+            }
+            _ => None,
         }
-
-        content
     }
 }
 
@@ -460,11 +585,78 @@ pub fn get_tab_window(
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeMap;
+
+    use chrono::Local;
     use zellij_tile::prelude::TabInfo;
 
-    use super::get_tab_window;
+    use super::{TabsWidget, get_tab_window};
+    use crate::{
+        config::{Hover, ZellijState},
+        widgets::widget::Widget,
+    };
     use rstest::rstest;
 
+    #[test]
+    fn test_hover_format() {
+        let widget = TabsWidget::new(&BTreeMap::from([
+            ("tab_normal".to_owned(), "{name} ".to_owned()),
+            ("tab_normal_hover".to_owned(), "{name}!".to_owned()),
+        ]));
+
+        let tab = |name: &str, position: usize, active: bool| TabInfo {
+            name: name.to_owned(),
+            position,
+            active,
+            ..TabInfo::default()
+        };
+
+        let mut state = ZellijState {
+            tabs: vec![tab("one", 0, false), tab("two", 1, true)],
+            ..ZellijState::default()
+        };
+        assert_eq!(widget.process("tabs", &state), "one two ");
+
+        let hover = |pos: usize| Hover {
+            key: "tabs".to_owned(),
+            pos,
+            tooltip: None,
+            since: Local::now(),
+            seen: Local::now(),
+        };
+
+        state.hover = Some(hover(1));
+        assert_eq!(widget.process("tabs", &state), "one!two ");
+        assert_eq!(widget.tooltip("tabs", &state, 1), Some("one".to_owned()));
+
+        // the active tab has no hover format
+        state.hover = Some(hover(5));
+        assert_eq!(widget.process("tabs", &state), "one two ");
+        assert_eq!(widget.tooltip("tabs", &state, 5), Some("two".to_owned()));
+        assert_eq!(widget.tooltip("tabs", &state, 9), None);
+
+        // hover formats of a different width move the following tabs
+        let widget = TabsWidget::new(&BTreeMap::from([
+            ("tab_normal".to_owned(), "{name} ".to_owned()),
+            ("tab_normal_hover".to_owned(), "[{name}] ".to_owned()),
+        ]));
+        state.tabs = vec![
+            tab("one", 0, false),
+            tab("two", 1, false),
+            tab("three", 2, true),
+        ];
+
+        state.hover = Some(hover(1));
+        assert_eq!(widget.process("tabs", &state), "[one] two three ");
+        assert_eq!(widget.tooltip("tabs", &state, 5), Some("one".to_owned()));
+        assert_eq!(widget.tooltip("tabs", &state, 9), Some("two".to_owned()));
+
+        state.hover = Some(hover(9));
+        assert_eq!(widget.process("tabs", &state), "one [two] three ");
+        assert_eq!(widget.tooltip("tabs", &state, 9), Some("two".to_owned()));
+        assert_eq!(widget.tooltip("tabs", &state, 10), Some("three".to_owned()));
+    }
+
     #[rstest]
     #[case(
         vec![
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Local};
+
+use crate::{
+    config::{Dependency, Hover, ZellijState},
+    render::FormattedPart,
+    widgets::widget::{MouseAction, Widget},
+};
+
+/// Default time in seconds, that a tooltip is shown for.
+const DEFAULT_DURATION: i64 = 3;
+
+/// Renders the details of the hovered widget with `tooltip_format`, e.g. the
+/// full name of a tab. They are shown for `tooltip_duration` seconds or until
+/// the cursor leaves the widget.
+pub struct TooltipWidget {
+    format: Vec<FormattedPart>,
+    duration: i64,
+}
+
+impl TooltipWidget {
+    pub fn new(config: &BTreeMap<String, String>) -> Self {
+        let format = match config.get("tooltip_format") {
+            Some(f) => FormattedPart::multiple_from_format_string(f, config),
+            None => FormattedPart::multiple_from_format_string("{text}", config),
+        };
+
+        let duration = match config.get("tooltip_duration") {
+            Some(d) => d.parse::<i64>().unwrap_or(DEFAULT_DURATION),
+            None => DEFAULT_DURATION,
+        };
+
+        Self { format, duration }
+    }
+
+    fn expires_at(&self, hover: &Hover) -> DateTime<Local> {
+        hover.since + Duration::seconds(self.duration)
+    }
+
+    fn visible_text<'a>(&self, state: &'a ZellijState) -> Option<&'a str> {
+        let hover = state.hover.as_ref()?;
+
+        if self.expires_at(hover) <= Local::now() {
+            return None;
+        }
+
+        hover.tooltip.as_deref()
+    }
+}
+
+impl Widget for TooltipWidget {
+    fn process(&self, _name: &str, state: &ZellijState) -> String {
+        let Some(text) = self.visible_text(state) else {
+            return "".to_owned();
+        };
+
+        let mut output = "".to_owned();
+
+        for f in self.format.iter() {
+            let content = match f.render_content(state, |key| match key {
+                "text" => Some(text.to_owned()),
+                _ => None,
+            }) {
+                Some(content) => content,
+                None => continue,
+            };
+
+            output = format!("{}{}", output, f.format_string(&content));
+        }
+
+        output
+    }
+
+    fn process_click(&self, _name: &str, _state: &ZellijState, _pos: usize, _action: MouseAction) {}
+
+    fn dependencies(&self, _name: &str) -> Vec<Dependency> {
+        vec![Dependency::Hover, Dependency::Mode, Dependency::Clock]
+    }
+
+    /// Keeps the tooltip, while the cursor moves onto it.
+    fn tooltip(&self, _name: &str, state: &ZellijState, _pos: usize) -> Option<String> {
+        self.visible_text(state).map(|text| text.to_owned())
+    }
+
+    fn next_update(&self, state: &ZellijState) -> Option<DateTime<Local>> {
+        let hover = state.hover.as_ref()?;
+        let expires_at = self.expires_at(hover);
+
+        match hover.tooltip.is_some() && expires_at > Local::now() {
+            true => Some(expires_at),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tooltip_expires() {
+        let widget = TooltipWidget::new(&BTreeMap::from([
+            ("tooltip_format".to_owned(), "[{text}]".to_owned()),
+            ("tooltip_duration".to_owned(), "2".to_owned()),
+        ]));
+
+        let mut state = ZellijState {
+            hover: Some(Hover {
+                key: "tabs".to_owned(),
+                pos: 0,
+                tooltip: Some("a long tab name".to_owned()),
+                since: Local::now(),
+                seen: Local::now(),
+            }),
+            ..ZellijState::default()
+        };
+
+        assert_eq!(widget.process("tooltip", &state), "[a long tab name]");
+        assert!(widget.next_update(&state).is_some());
+
+        state.hover.as_mut().unwrap().since = Local::now() - Duration::seconds(3);
+        assert_eq!(widget.process("tooltip", &state), "");
+        assert!(widget.next_update(&state).is_none());
+
+        state.hover = None;
+        assert_eq!(widget.process("tooltip", &state), "");
+    }
+}
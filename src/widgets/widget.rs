@@ -1,6 +1,57 @@
-use crate::config::ZellijState;
+use std::any::Any;
+
+use chrono::{DateTime, Local};
+
+use crate::config::{Dependency, ZellijState};
+
+/// Mouse event, that is passed to the widget under the cursor.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum MouseAction {
+    LeftClick,
+    RightClick,
+    ScrollUp,
+    ScrollDown,
+}
 
 pub trait Widget {
     fn process(&self, name: &str, state: &ZellijState) -> String;
-    fn process_click(&self, name: &str, state: &ZellijState, pos: usize);
+
+    /// Handles a mouse event at the offset `pos` into the output of the
+    /// widget.
+    fn process_click(&self, name: &str, state: &ZellijState, pos: usize, action: MouseAction);
+
+    /// Returns the parts of the state, that the output of the widget with the
+    /// given name depends on. Cached output is only rendered again, when one
+    /// of them changed.
+    fn dependencies(&self, name: &str) -> Vec<Dependency>;
+
+    /// Returns details, that are shown by `{tooltip}` while the cursor is
+    /// over the widget, e.g. the full name of the tab at `pos`.
+    fn tooltip(&self, _name: &str, _state: &ZellijState, _pos: usize) -> Option<String> {
+        None
+    }
+
+    /// Returns the dependencies, that changed since time passed, e.g. commands
+    /// whose interval is due. It is called whenever a timer fires, widgets
+    /// that change on every timer depend on [`Dependency::Clock`] instead.
+    fn expired(&self, _state: &ZellijState) -> Vec<Dependency> {
+        Vec::new()
+    }
+
+    /// Returns when the output of the widget changes without any event, e.g.
+    /// for the clock to tick. The bar is rendered again at the earliest time
+    /// of all widgets.
+    fn next_update(&self, _state: &ZellijState) -> Option<DateTime<Local>> {
+        None
+    }
+
+    /// Returns the state, that the widget built up since it has been created,
+    /// e.g. the commands that are running. It is passed to
+    /// [`Widget::restore_runtime_state`] of the widget, that replaces this one
+    /// when the configuration is parsed again.
+    fn runtime_state(&self) -> Option<Box<dyn Any>> {
+        None
+    }
+
+    fn restore_runtime_state(&self, _state: Box<dyn Any>) {}
 }